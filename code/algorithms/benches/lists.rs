@@ -0,0 +1,295 @@
+// Compares the list types on the same operations, at sizes from 10 to a million elements.
+//
+//     cargo bench
+//     cargo bench -- push_front          only the push_front group
+//
+// The report, with a chart per operation of time against size for every list type,
+// ends up in target/criterion/report/index.html
+//
+// Each benchmark times one operation on a list that already holds `size` elements,
+// and undoes it afterwards (push_front is followed by pop_back, and so on), so the
+// list stays the same size however many iterations criterion decides to run.
+use algorithms::buffer::{GapBuffer, RingBuffer};
+use algorithms::linked_list::{LinkedList, LinkedListAsVec};
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion};
+use std::collections::{LinkedList as StdLinkedList, VecDeque};
+use std::hint::black_box;
+use std::time::Duration;
+
+const SIZES: [usize; 6] = [10, 100, 1_000, 10_000, 100_000, 1_000_000];
+
+// The operations every list type is measured on
+trait BenchList: FromIterator<u64> {
+    const NAME: &'static str;
+
+    fn push_front(&mut self, value: u64);
+    fn push_back(&mut self, value: u64);
+    fn pop_front(&mut self) -> Option<u64>;
+    fn pop_back(&mut self) -> Option<u64>;
+    fn insert(&mut self, index: usize, value: u64);
+    fn remove(&mut self, index: usize) -> Option<u64>;
+    fn sum(&self) -> u64;
+
+    fn filled(size: usize) -> Self {
+        (0..size as u64).collect()
+    }
+}
+
+// LinkedListAsVec, GapBuffer and RingBuffer have the same methods
+macro_rules! vec_like {
+    ($list:ident) => {
+        impl BenchList for $list<u64> {
+            const NAME: &'static str = stringify!($list);
+
+            fn push_front(&mut self, value: u64) {
+                $list::push_front(self, value)
+            }
+            fn push_back(&mut self, value: u64) {
+                $list::push_back(self, value)
+            }
+            fn pop_front(&mut self) -> Option<u64> {
+                $list::pop_front(self)
+            }
+            fn pop_back(&mut self) -> Option<u64> {
+                $list::pop_back(self)
+            }
+            fn insert(&mut self, index: usize, value: u64) {
+                self.insert_at_index(index, value).unwrap()
+            }
+            fn remove(&mut self, index: usize) -> Option<u64> {
+                $list::remove(self, index)
+            }
+            fn sum(&self) -> u64 {
+                self.iter().sum()
+            }
+        }
+    };
+}
+
+vec_like!(LinkedListAsVec);
+vec_like!(GapBuffer);
+vec_like!(RingBuffer);
+
+impl BenchList for LinkedList<u64> {
+    const NAME: &'static str = "LinkedList";
+
+    fn push_front(&mut self, value: u64) {
+        LinkedList::push_front(self, value)
+    }
+    fn push_back(&mut self, value: u64) {
+        LinkedList::push_back(self, value)
+    }
+    fn pop_front(&mut self) -> Option<u64> {
+        LinkedList::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<u64> {
+        LinkedList::pop_back(self)
+    }
+    // A cursor walked from the nearer end, the walk is what makes this O(n)
+    fn insert(&mut self, index: usize, value: u64) {
+        let len = self.len();
+        if index <= len / 2 {
+            let mut cursor = self.cursor_front_mut();
+            for _ in 0..index {
+                cursor.move_next();
+            }
+            cursor.insert_before(value);
+        } else {
+            let mut cursor = self.cursor_back_mut();
+            for _ in index..len {
+                cursor.move_prev();
+            }
+            cursor.insert_after(value);
+        }
+    }
+    fn remove(&mut self, index: usize) -> Option<u64> {
+        let len = self.len();
+        if index <= len / 2 {
+            let mut cursor = self.cursor_front_mut();
+            for _ in 0..index {
+                cursor.move_next();
+            }
+            cursor.remove_current()
+        } else {
+            let mut cursor = self.cursor_back_mut();
+            for _ in index + 1..len {
+                cursor.move_prev();
+            }
+            cursor.remove_current()
+        }
+    }
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+}
+
+impl BenchList for StdLinkedList<u64> {
+    const NAME: &'static str = "std LinkedList";
+
+    fn push_front(&mut self, value: u64) {
+        StdLinkedList::push_front(self, value)
+    }
+    fn push_back(&mut self, value: u64) {
+        StdLinkedList::push_back(self, value)
+    }
+    fn pop_front(&mut self) -> Option<u64> {
+        StdLinkedList::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<u64> {
+        StdLinkedList::pop_back(self)
+    }
+    // std's cursors are unstable, split_off walks from the nearer end instead
+    fn insert(&mut self, index: usize, value: u64) {
+        let mut tail = self.split_off(index);
+        self.push_back(value);
+        self.append(&mut tail);
+    }
+    fn remove(&mut self, index: usize) -> Option<u64> {
+        let mut tail = self.split_off(index);
+        let value = tail.pop_front();
+        self.append(&mut tail);
+        value
+    }
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+}
+
+impl BenchList for VecDeque<u64> {
+    const NAME: &'static str = "VecDeque";
+
+    fn push_front(&mut self, value: u64) {
+        VecDeque::push_front(self, value)
+    }
+    fn push_back(&mut self, value: u64) {
+        VecDeque::push_back(self, value)
+    }
+    fn pop_front(&mut self) -> Option<u64> {
+        VecDeque::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<u64> {
+        VecDeque::pop_back(self)
+    }
+    fn insert(&mut self, index: usize, value: u64) {
+        VecDeque::insert(self, index, value)
+    }
+    fn remove(&mut self, index: usize) -> Option<u64> {
+        VecDeque::remove(self, index)
+    }
+    fn sum(&self) -> u64 {
+        self.iter().sum()
+    }
+}
+
+fn push_front<L: BenchList>(group: &mut BenchmarkGroup<WallTime>, size: usize) {
+    let mut list = L::filled(size);
+    group.bench_function(BenchmarkId::new(L::NAME, size), |b| {
+        b.iter(|| {
+            list.push_front(black_box(7));
+            list.pop_back()
+        })
+    });
+}
+
+fn push_back<L: BenchList>(group: &mut BenchmarkGroup<WallTime>, size: usize) {
+    let mut list = L::filled(size);
+    group.bench_function(BenchmarkId::new(L::NAME, size), |b| {
+        b.iter(|| {
+            list.push_back(black_box(7));
+            list.pop_back()
+        })
+    });
+}
+
+fn pop_front<L: BenchList>(group: &mut BenchmarkGroup<WallTime>, size: usize) {
+    let mut list = L::filled(size);
+    group.bench_function(BenchmarkId::new(L::NAME, size), |b| {
+        b.iter(|| {
+            let value = list.pop_front().unwrap();
+            list.push_back(black_box(value));
+        })
+    });
+}
+
+// Inserts at a pseudo random index, then removes the element again.
+// Every list type sees the same sequence of indexes.
+fn random_insert<L: BenchList>(group: &mut BenchmarkGroup<WallTime>, size: usize) {
+    let mut list = L::filled(size);
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    group.bench_function(BenchmarkId::new(L::NAME, size), |b| {
+        b.iter(|| {
+            // xorshift, good enough to spread the indexes and needs no extra crate
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let index = (state % (size as u64 + 1)) as usize;
+
+            list.insert(black_box(index), 7);
+            list.remove(index)
+        })
+    });
+}
+
+// What an editor does: a cursor wanders back and forth a few places at a time,
+// and every edit happens where it is
+fn insert_near_cursor<L: BenchList>(group: &mut BenchmarkGroup<WallTime>, size: usize) {
+    let mut list = L::filled(size);
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut cursor = size / 2;
+    group.bench_function(BenchmarkId::new(L::NAME, size), |b| {
+        b.iter(|| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            cursor = (cursor + (state % 17) as usize).saturating_sub(8).min(size);
+
+            list.insert(black_box(cursor), 7);
+            list.remove(cursor)
+        })
+    });
+}
+
+fn iterate<L: BenchList>(group: &mut BenchmarkGroup<WallTime>, size: usize) {
+    let list = L::filled(size);
+    group.bench_function(BenchmarkId::new(L::NAME, size), |b| {
+        b.iter(|| black_box(&list).sum())
+    });
+}
+
+// One group per operation, with every list type at every size, so the report
+// draws them on the same chart
+macro_rules! compare {
+    ($criterion:expr, $bench:ident) => {{
+        let mut group = $criterion.benchmark_group(stringify!($bench));
+        for size in SIZES {
+            $bench::<LinkedListAsVec<u64>>(&mut group, size);
+            $bench::<GapBuffer<u64>>(&mut group, size);
+            $bench::<RingBuffer<u64>>(&mut group, size);
+            $bench::<LinkedList<u64>>(&mut group, size);
+            $bench::<StdLinkedList<u64>>(&mut group, size);
+            $bench::<VecDeque<u64>>(&mut group, size);
+        }
+        group.finish();
+    }};
+}
+
+fn lists(c: &mut Criterion) {
+    compare!(c, push_front);
+    compare!(c, push_back);
+    compare!(c, pop_front);
+    compare!(c, random_insert);
+    compare!(c, insert_near_cursor);
+    compare!(c, iterate);
+}
+
+criterion_group! {
+    name = benches;
+    // 216 benchmarks, so each one gets a short run. Plenty to tell O(1) from O(n).
+    config = Criterion::default()
+        .warm_up_time(Duration::from_millis(300))
+        .measurement_time(Duration::from_secs(1))
+        .sample_size(20);
+    targets = lists
+}
+criterion_main!(benches);
@@ -0,0 +1,349 @@
+// A gap buffer keeps its elements at the two ends of one block of memory,
+// with the free space, the gap, in between:
+//
+//     [a b c _ _ _ _ d e]        gap_start = 3, gap_end = 7
+//            ^ the gap starts at index 3 of the list
+//
+// Inserting or removing at the gap only moves gap_start or gap_end, so it is O(1).
+// Anywhere else, the gap is moved there first, by moving the elements in between
+// across it. An edit costs the distance from the previous one, not the length of the list:
+// typing at a cursor is O(1) per key however long the text is.
+//
+// The flip side is jumping around. Pushing at the front and popping at the back
+// moves every element across the gap each time, which is no better than LinkedListAsVec.
+// Reading never moves the gap.
+
+use super::{IntoIter, Iter, IterMut};
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::slice;
+
+pub struct GapBuffer<T> {
+    buf: Box<[MaybeUninit<T>]>,
+    gap_start: usize,
+    gap_end: usize,
+}
+
+list_traits!(GapBuffer);
+
+impl<T> GapBuffer<T> {
+    pub fn new() -> GapBuffer<T> {
+        GapBuffer::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> GapBuffer<T> {
+        GapBuffer {
+            buf: Box::new_uninit_slice(capacity),
+            gap_start: 0,
+            gap_end: capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn gap_len(&self) -> usize {
+        self.gap_end - self.gap_start
+    }
+
+    pub fn len(&self) -> usize {
+        self.capacity() - self.gap_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The index the gap is at, inserting there is free
+    pub fn gap(&self) -> usize {
+        self.gap_start
+    }
+
+    // Makes room for at least `additional` more elements.
+    // The block at least doubles, so pushing one at a time is O(1) amortized.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.gap_len() >= additional {
+            return;
+        }
+
+        let needed = self
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        let capacity = needed.max(self.capacity() * 2).max(4);
+        let mut buf = Box::<[T]>::new_uninit_slice(capacity);
+
+        // The elements before the gap go to the start of the new block and the ones
+        // after it to the end, the new space all goes into the gap
+        let tail = self.capacity() - self.gap_end;
+        let gap_end = capacity - tail;
+        // SAFETY: both runs fit in the new block, which doesn't overlap the old one.
+        // The old block is dropped as plain MaybeUninits, so the elements are moved, not copied.
+        unsafe {
+            let old = self.buf.as_ptr();
+            let new = buf.as_mut_ptr();
+            ptr::copy_nonoverlapping(old, new, self.gap_start);
+            ptr::copy_nonoverlapping(old.add(self.gap_end), new.add(gap_end), tail);
+        }
+
+        self.buf = buf;
+        self.gap_end = gap_end;
+    }
+
+    // Moves the gap to start at `index`, by moving the elements in between
+    // to the other side of it
+    fn move_gap(&mut self, index: usize) {
+        debug_assert!(index <= self.len());
+        let base = self.buf.as_mut_ptr();
+
+        if index < self.gap_start {
+            // [a b c d _ _] -> [a b _ _ c d]     (index = 2)
+            let count = self.gap_start - index;
+            // SAFETY: both ranges are inside the block, ptr::copy allows them to overlap
+            unsafe { ptr::copy(base.add(index), base.add(self.gap_end - count), count) };
+            self.gap_start = index;
+            self.gap_end -= count;
+        } else if index > self.gap_start {
+            // [a b _ _ c d] -> [a b c d _ _]     (index = 4)
+            let count = index - self.gap_start;
+            // SAFETY: as above
+            unsafe { ptr::copy(base.add(self.gap_end), base.add(self.gap_start), count) };
+            self.gap_start = index;
+            self.gap_end += count;
+        }
+    }
+
+    // Where the element at `index` is in the block
+    fn slot(&self, index: usize) -> usize {
+        if index < self.gap_start {
+            index
+        } else {
+            index + self.gap_len()
+        }
+    }
+
+    // Expects index <= len
+    fn insert(&mut self, index: usize, value: T) {
+        self.reserve(1);
+        self.move_gap(index);
+        self.buf[self.gap_start].write(value);
+        self.gap_start += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        self.insert(self.len(), value);
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        self.insert(0, value);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(0)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let last = self.len().checked_sub(1)?;
+        self.remove(last)
+    }
+
+    // The elements before the gap, and the ones after it
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, rest) = self.buf.split_at(self.gap_start);
+        let back = &rest[self.gap_len()..];
+        // SAFETY: the slots on either side of the gap hold elements
+        unsafe {
+            (
+                slice::from_raw_parts(front.as_ptr().cast(), front.len()),
+                slice::from_raw_parts(back.as_ptr().cast(), back.len()),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let gap_len = self.gap_len();
+        let (front, rest) = self.buf.split_at_mut(self.gap_start);
+        let back = &mut rest[gap_len..];
+        // SAFETY: as above
+        unsafe {
+            (
+                slice::from_raw_parts_mut(front.as_mut_ptr().cast(), front.len()),
+                slice::from_raw_parts_mut(back.as_mut_ptr().cast(), back.len()),
+            )
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.as_slices())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::new(self.as_mut_slices())
+    }
+
+    // None for any index past the end, not just on an empty list
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        // SAFETY: the slot of an index below len holds an element
+        Some(unsafe { self.buf[self.slot(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len() {
+            return None;
+        }
+        let slot = self.slot(index);
+        // SAFETY: as above
+        Some(unsafe { self.buf[slot].assume_init_mut() })
+    }
+
+    // The last element, like `back`
+    pub fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+
+    // The first element, like `front`
+    pub fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len().checked_sub(1)?)
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|element| element == value)
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    // Keeps the first `len` elements and drops the rest, does nothing if the list is shorter
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+
+        // Everything after the gap goes, and the gap grows over it before anything
+        // is dropped, so a panicking drop leaks the rest instead of dropping it twice
+        self.move_gap(len);
+        let start = self.gap_end;
+        self.gap_end = self.capacity();
+        // SAFETY: the slots from start to the end of the block held elements until now
+        unsafe {
+            let tail = self.buf.as_mut_ptr().add(start).cast::<T>();
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(tail, self.gap_end - start));
+        }
+    }
+
+    // Splits the list in two at `at`. The list keeps the elements before `at`
+    // and the returned list gets the rest. None if `at` is past the end.
+    pub fn split_off(&mut self, at: usize) -> Option<GapBuffer<T>> {
+        if at > self.len() {
+            return None;
+        }
+
+        self.move_gap(at);
+        let tail = self.capacity() - self.gap_end;
+        let mut other = GapBuffer::with_capacity(tail);
+        // SAFETY: the elements after the gap move to the other list, and the gap
+        // grows over them so this list no longer owns them
+        unsafe {
+            let from = self.buf.as_ptr().add(self.gap_end);
+            ptr::copy_nonoverlapping(from, other.buf.as_mut_ptr(), tail);
+        }
+        other.gap_start = tail;
+        self.gap_end = self.capacity();
+        Some(other)
+    }
+
+    // Moves all the elements of `other` to the back of this list, leaving `other` empty
+    pub fn append(&mut self, other: &mut GapBuffer<T>) {
+        self.reserve(other.len());
+        self.move_gap(self.len());
+
+        let (front, back) = other.as_slices();
+        let moved = front.len() + back.len();
+        // SAFETY: the gap has room for all of them, and `other` gives them up below
+        unsafe {
+            let to = self.buf.as_mut_ptr().add(self.gap_start).cast::<T>();
+            ptr::copy_nonoverlapping(front.as_ptr(), to, front.len());
+            ptr::copy_nonoverlapping(back.as_ptr(), to.add(front.len()), back.len());
+        }
+        self.gap_start += moved;
+
+        // The gap takes over all of `other`, its elements now belong to this list
+        other.gap_start = 0;
+        other.gap_end = other.capacity();
+    }
+
+    // Inserting past the end fails and hands the value back
+    pub fn insert_at_index(&mut self, index: usize, value: T) -> Result<(), T> {
+        if index > self.len() {
+            return Err(value);
+        }
+
+        self.insert(index, value);
+        Ok(())
+    }
+
+    // Removes the element at `at`, None if there is no such element
+    pub fn remove(&mut self, at: usize) -> Option<T> {
+        if at >= self.len() {
+            return None;
+        }
+
+        // With the gap at `at`, the element is the first one after it.
+        // The gap grows over it and it is moved out.
+        self.move_gap(at);
+        // SAFETY: the slot held an element, and now it's part of the gap it won't be read again
+        let value = unsafe { self.buf[self.gap_end].assume_init_read() };
+        self.gap_end += 1;
+        Some(value)
+    }
+}
+
+impl<T> Drop for GapBuffer<T> {
+    fn drop(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        // SAFETY: the slices hold the elements, which are never used again
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(back);
+        }
+    }
+}
+
+impl<T> IntoIterator for GapBuffer<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> IntoIter<T> {
+        let front = 0..self.gap_start;
+        let back = self.gap_end..self.capacity();
+        // The iterator takes the block, and leaves an empty one behind for `self` to drop
+        let buf = mem::take(&mut self.buf);
+        self.gap_start = 0;
+        self.gap_end = 0;
+        IntoIter::new(buf, front, back)
+    }
+}
@@ -0,0 +1,315 @@
+// Two more lists that keep their elements in one block of memory, for workloads
+// where LinkedListAsVec, which moves every element to push at the front, is too slow.
+//
+// GapBuffer keeps the free space as a gap where the last edit happened, so edits
+// close to each other are cheap. This is how text editors store the text being edited.
+// RingBuffer lets the elements wrap around the end of the block, so both ends
+// are O(1), like std's VecDeque.
+//
+// Both have the same methods as LinkedListAsVec, so they can be swapped for it,
+// and `cargo bench` compares them all.
+//
+// Some slots of the block hold elements and the rest are uninitialized, so the
+// block is a Box<[MaybeUninit<T>]> and the unsafe blocks below keep track of which is which:
+// - a slot is only read, or handed out as a reference, while it holds an element
+// - an element is moved out or dropped exactly once, when it leaves the list
+
+use std::iter::FusedIterator;
+use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::slice;
+
+// The trait impls that only need the list's own methods, written once for both lists
+macro_rules! list_traits {
+    ($list:ident) => {
+        impl<T> Default for $list<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T: std::fmt::Debug> std::fmt::Debug for $list<T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_list().entries(self).finish()
+            }
+        }
+
+        impl<T: Clone> Clone for $list<T> {
+            fn clone(&self) -> Self {
+                self.iter().cloned().collect()
+            }
+        }
+
+        // Element by element, like a Vec, wherever the gap or the head happens to be
+        impl<T: PartialEq> PartialEq for $list<T> {
+            fn eq(&self, other: &Self) -> bool {
+                self.len() == other.len() && self.iter().eq(other)
+            }
+        }
+
+        impl<T: Eq> Eq for $list<T> {}
+
+        impl<T: PartialOrd> PartialOrd for $list<T> {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                self.iter().partial_cmp(other)
+            }
+        }
+
+        impl<T: Ord> Ord for $list<T> {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.iter().cmp(other)
+            }
+        }
+
+        impl<T: std::hash::Hash> std::hash::Hash for $list<T> {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                state.write_usize(self.len());
+                for value in self {
+                    value.hash(state);
+                }
+            }
+        }
+
+        impl<T> FromIterator<T> for $list<T> {
+            fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+                let mut list = Self::new();
+                list.extend(iter);
+                list
+            }
+        }
+
+        impl<T> Extend<T> for $list<T> {
+            fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+                let iter = iter.into_iter();
+                self.reserve(iter.size_hint().0);
+                for value in iter {
+                    self.push_back(value);
+                }
+            }
+        }
+
+        impl<'a, T: Copy + 'a> Extend<&'a T> for $list<T> {
+            fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+                self.extend(iter.into_iter().copied());
+            }
+        }
+
+        impl<'a, T> IntoIterator for &'a $list<T> {
+            type Item = &'a T;
+            type IntoIter = super::Iter<'a, T>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'a, T> IntoIterator for &'a mut $list<T> {
+            type Item = &'a mut T;
+            type IntoIter = super::IterMut<'a, T>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+
+        // list[i] panics past the end, like it does for a Vec. Use `get` to check first.
+        impl<T> std::ops::Index<usize> for $list<T> {
+            type Output = T;
+
+            fn index(&self, index: usize) -> &T {
+                let len = self.len();
+                match self.get(index) {
+                    Some(value) => value,
+                    None => {
+                        panic!("index out of bounds: the len is {len} but the index is {index}")
+                    }
+                }
+            }
+        }
+
+        impl<T> std::ops::IndexMut<usize> for $list<T> {
+            fn index_mut(&mut self, index: usize) -> &mut T {
+                let len = self.len();
+                match self.get_mut(index) {
+                    Some(value) => value,
+                    None => {
+                        panic!("index out of bounds: the len is {len} but the index is {index}")
+                    }
+                }
+            }
+        }
+
+        // A plain sequence, the same as LinkedListAsVec
+        #[cfg(feature = "serde")]
+        impl<T: serde::Serialize> serde::Serialize for $list<T> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(self)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for $list<T> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Vec::<T>::deserialize(deserializer).map(|values| values.into_iter().collect())
+            }
+        }
+    };
+}
+
+mod gap_buffer;
+mod ring_buffer;
+
+pub use gap_buffer::GapBuffer;
+pub use ring_buffer::RingBuffer;
+
+// Both lists hold their elements in at most two runs, each contiguous in memory.
+// The iterators walk the first run and then the second.
+pub struct Iter<'a, T> {
+    first: slice::Iter<'a, T>,
+    second: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn new((first, second): (&'a [T], &'a [T])) -> Self {
+        Iter {
+            first: first.iter(),
+            second: second.iter(),
+        }
+    }
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            first: self.first.clone(),
+            second: self.second.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.first.next().or_else(|| self.second.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.first.len() + self.second.len();
+        (len, Some(len))
+    }
+
+    // sum(), for_each() and friends go through fold, doing each run in one go
+    // lets them run as tight as they would over a slice
+    fn fold<B, F: FnMut(B, Self::Item) -> B>(self, init: B, mut f: F) -> B {
+        let acc = self.first.fold(init, &mut f);
+        self.second.fold(acc, f)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.second.next_back().or_else(|| self.first.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    first: slice::IterMut<'a, T>,
+    second: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    fn new((first, second): (&'a mut [T], &'a mut [T])) -> Self {
+        IterMut {
+            first: first.iter_mut(),
+            second: second.iter_mut(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.first.next().or_else(|| self.second.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.first.len() + self.second.len();
+        (len, Some(len))
+    }
+
+    // As for Iter
+    fn fold<B, F: FnMut(B, Self::Item) -> B>(self, init: B, mut f: F) -> B {
+        let acc = self.first.fold(init, &mut f);
+        self.second.fold(acc, f)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        self.second.next_back().or_else(|| self.first.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+// Takes the block over from the list, along with where the two runs of elements are.
+// The slots outside the ranges are free or already moved out.
+pub struct IntoIter<T> {
+    buf: Box<[MaybeUninit<T>]>,
+    first: Range<usize>,
+    second: Range<usize>,
+}
+
+impl<T> IntoIter<T> {
+    fn new(buf: Box<[MaybeUninit<T>]>, first: Range<usize>, second: Range<usize>) -> Self {
+        IntoIter { buf, first, second }
+    }
+
+    // SAFETY: the slot must hold an element, and must not be read again
+    unsafe fn take(&mut self, slot: usize) -> T {
+        unsafe { self.buf[slot].assume_init_read() }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let slot = self.first.next().or_else(|| self.second.next())?;
+        // SAFETY: the ranges only cover elements, and the slot just left them
+        Some(unsafe { self.take(slot) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.first.len() + self.second.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        let slot = self.second.next_back().or_else(|| self.first.next_back())?;
+        // SAFETY: the ranges only cover elements, and the slot just left them
+        Some(unsafe { self.take(slot) })
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+// Drops whatever wasn't iterated. The block itself is freed with `buf`,
+// dropping a MaybeUninit never drops what is in it.
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for _ in &mut *self {}
+    }
+}
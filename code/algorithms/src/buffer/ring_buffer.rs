@@ -0,0 +1,371 @@
+// A ring buffer starts its elements at `head` and wraps around to the beginning
+// of the block when they reach the end of it:
+//
+//     [d e _ _ _ a b c]        head = 5, len = 5
+//
+// Pushing at the front moves `head` one place back, and pushing at the back writes
+// just past the last element, so both ends are O(1) and nothing else moves.
+// When the block is full it doubles, and the elements are laid out from the start again.
+//
+// Inserting or removing in the middle moves the elements on the shorter side of
+// the index by one place, so it is O(min(i, n - i)). Near either end it's cheap,
+// in the middle it moves half the list.
+
+use super::{IntoIter, Iter, IterMut};
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::slice;
+
+pub struct RingBuffer<T> {
+    buf: Box<[MaybeUninit<T>]>,
+    head: usize,
+    len: usize,
+}
+
+list_traits!(RingBuffer);
+
+impl<T> RingBuffer<T> {
+    pub fn new() -> RingBuffer<T> {
+        RingBuffer::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> RingBuffer<T> {
+        RingBuffer {
+            buf: Box::new_uninit_slice(capacity),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Makes room for at least `additional` more elements.
+    // The block at least doubles, so pushing one at a time is O(1) amortized.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.capacity() - self.len >= additional {
+            return;
+        }
+
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        let capacity = needed.max(self.capacity() * 2).max(4);
+        let mut buf = Box::<[T]>::new_uninit_slice(capacity);
+
+        // Unwrap the elements to the start of the new block
+        let (front, back) = self.as_slices();
+        // SAFETY: both runs fit in the new block, which doesn't overlap the old one.
+        // The old block is dropped as plain MaybeUninits, so the elements are moved, not copied.
+        unsafe {
+            let new = buf.as_mut_ptr().cast::<T>();
+            ptr::copy_nonoverlapping(front.as_ptr(), new, front.len());
+            ptr::copy_nonoverlapping(back.as_ptr(), new.add(front.len()), back.len());
+        }
+
+        self.buf = buf;
+        self.head = 0;
+    }
+
+    // Where the element at `index` is in the block. Works up to index == capacity,
+    // which is all the wrapping an index below len ever needs.
+    fn slot(&self, index: usize) -> usize {
+        let slot = self.head + index;
+        if slot >= self.capacity() {
+            slot - self.capacity()
+        } else {
+            slot
+        }
+    }
+
+    // Moves the elements at indexes start..end one place on (`forward`) or one place back.
+    // The run can wrap around the end of the block, so it is copied in up to three
+    // pieces, each contiguous both where it comes from and where it goes to.
+    // Going forward the pieces are copied from the end of the run, so each one only
+    // overwrites slots that have already been moved out of, and the other way round going back.
+    // SAFETY: the slot the run moves into, past one end of it, must be free
+    unsafe fn shift(&mut self, mut start: usize, mut end: usize, forward: bool) {
+        let capacity = self.capacity();
+        let base = self.buf.as_mut_ptr();
+
+        while start < end {
+            if forward {
+                let from = self.slot(end - 1) + 1;
+                let to = self.slot(end) + 1;
+                let count = (end - start).min(from).min(to);
+                unsafe { ptr::copy(base.add(from - count), base.add(to - count), count) };
+                end -= count;
+            } else {
+                let from = self.slot(start);
+                let to = self.slot(start - 1);
+                let count = (end - start).min(capacity - from).min(capacity - to);
+                unsafe { ptr::copy(base.add(from), base.add(to), count) };
+                start += count;
+            }
+        }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        self.reserve(1);
+        let slot = self.slot(self.len);
+        self.buf[slot].write(value);
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        self.reserve(1);
+        self.head = self.slot(self.capacity() - 1);
+        self.buf[self.head].write(value);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: the head slot holds the first element, which leaves the list here
+        let value = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.slot(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        // SAFETY: the slot holds the last element, which leaves the list here
+        Some(unsafe { self.buf[self.slot(self.len)].assume_init_read() })
+    }
+
+    // The elements from head to the end of the block, and the ones that wrapped around
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let wrapped = (self.head + self.len).saturating_sub(self.capacity());
+        let front = &self.buf[self.head..self.head + self.len - wrapped];
+        let back = &self.buf[..wrapped];
+        // SAFETY: these are exactly the slots that hold elements
+        unsafe {
+            (
+                slice::from_raw_parts(front.as_ptr().cast(), front.len()),
+                slice::from_raw_parts(back.as_ptr().cast(), back.len()),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let wrapped = (self.head + self.len).saturating_sub(self.capacity());
+        let unwrapped = self.len - wrapped;
+        let (back, rest) = self.buf.split_at_mut(self.head);
+        let front = &mut rest[..unwrapped];
+        let back = &mut back[..wrapped];
+        // SAFETY: as above
+        unsafe {
+            (
+                slice::from_raw_parts_mut(front.as_mut_ptr().cast(), front.len()),
+                slice::from_raw_parts_mut(back.as_mut_ptr().cast(), back.len()),
+            )
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.as_slices())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::new(self.as_mut_slices())
+    }
+
+    // None for any index past the end, not just on an empty list
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        // SAFETY: the slot of an index below len holds an element
+        Some(unsafe { self.buf[self.slot(index)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let slot = self.slot(index);
+        // SAFETY: as above
+        Some(unsafe { self.buf[slot].assume_init_mut() })
+    }
+
+    // The last element, like `back`
+    pub fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+
+    // The first element, like `front`
+    pub fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|element| element == value)
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    // Keeps the first `len` elements and drops the rest, does nothing if the list is shorter
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        // Shorten the list before dropping anything, so a panicking drop
+        // leaks the rest instead of dropping it twice
+        let old_len = mem::replace(&mut self.len, len);
+        for index in len..old_len {
+            let slot = self.slot(index);
+            // SAFETY: the slot held an element until the list was shortened
+            unsafe { self.buf[slot].assume_init_drop() };
+        }
+    }
+
+    // Splits the list in two at `at`. The list keeps the elements before `at`
+    // and the returned list gets the rest. None if `at` is past the end.
+    pub fn split_off(&mut self, at: usize) -> Option<RingBuffer<T>> {
+        if at > self.len {
+            return None;
+        }
+
+        let mut other = RingBuffer::with_capacity(self.len - at);
+        for index in at..self.len {
+            let from = self.slot(index);
+            // SAFETY: the element moves to the other list, and this one is shortened below
+            unsafe {
+                let to = other.buf.as_mut_ptr().add(index - at);
+                ptr::copy_nonoverlapping(self.buf.as_ptr().add(from), to, 1);
+            }
+        }
+        other.len = self.len - at;
+        self.len = at;
+        Some(other)
+    }
+
+    // Moves all the elements of `other` to the back of this list, leaving `other` empty
+    pub fn append(&mut self, other: &mut RingBuffer<T>) {
+        self.reserve(other.len);
+
+        let (front, back) = other.as_slices();
+        for value in front.iter().chain(back) {
+            let slot = self.slot(self.len);
+            // SAFETY: there is room at the back, and `other` gives its elements up below
+            unsafe { ptr::copy_nonoverlapping(value, self.buf[slot].as_mut_ptr(), 1) };
+            self.len += 1;
+        }
+
+        other.head = 0;
+        other.len = 0;
+    }
+
+    // Inserting past the end fails and hands the value back
+    pub fn insert_at_index(&mut self, index: usize, value: T) -> Result<(), T> {
+        if index > self.len {
+            return Err(value);
+        }
+
+        self.reserve(1);
+        if index < self.len - index {
+            // Closer to the front: head moves back into the free slot before it,
+            // and the elements before the index follow it
+            self.head = self.slot(self.capacity() - 1);
+            // SAFETY: the slot before the run is the free one head just moved into
+            unsafe { self.shift(1, index + 1, false) };
+        } else {
+            // Closer to the back: the elements from the index on move one place forward
+            // SAFETY: the slot after the last element is free, reserve made sure of it
+            unsafe { self.shift(index, self.len, true) };
+        }
+
+        let slot = self.slot(index);
+        self.buf[slot].write(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    // Removes the element at `at`, None if there is no such element
+    pub fn remove(&mut self, at: usize) -> Option<T> {
+        if at >= self.len {
+            return None;
+        }
+
+        // SAFETY: the element leaves the list, and its slot is filled or freed below
+        let value = unsafe { self.buf[self.slot(at)].assume_init_read() };
+
+        if at < self.len - 1 - at {
+            // Closer to the front: the elements before it move one place on, over the hole
+            // SAFETY: the slot after the run is the one just read from
+            unsafe { self.shift(0, at, true) };
+            self.head = self.slot(1);
+        } else {
+            // Closer to the back: the elements after it move one place back
+            // SAFETY: as above, the slot before the run
+            unsafe { self.shift(at + 1, self.len, false) };
+        }
+
+        self.len -= 1;
+        Some(value)
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        // SAFETY: the slices hold the elements, which are never used again
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(back);
+        }
+    }
+}
+
+impl<T> IntoIterator for RingBuffer<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> IntoIter<T> {
+        let wrapped = (self.head + self.len).saturating_sub(self.capacity());
+        let front = self.head..self.head + self.len - wrapped;
+        let back = 0..wrapped;
+        // The iterator takes the block, and leaves an empty one behind for `self` to drop
+        let buf = mem::take(&mut self.buf);
+        self.head = 0;
+        self.len = 0;
+        IntoIter::new(buf, front, back)
+    }
+}
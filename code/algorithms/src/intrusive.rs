@@ -0,0 +1,420 @@
+// An intrusive doubly linked list: the prev and next pointers live in the values
+// themselves, in a Link field, instead of in nodes allocated by the list.
+//
+//     struct Timer {
+//         deadline: u64,
+//         link: Link<Timer>,
+//     }
+//
+//     unsafe impl Linked for Timer {
+//         fn link(&self) -> &Link<Timer> {
+//             &self.link
+//         }
+//     }
+//
+// A value joins the list as a Pin<Rc<T>>, and the list holds on to that Rc until
+// the value leaves again, so a linked value is never freed or moved. Anybody else
+// can keep their own Rc to it, in a HashMap say, and `remove(&value)` unlinks it
+// in O(1) without looking for it in the list. That's what LRU caches and timer
+// wheels need: find the entry by its key, then move it or take it out of the list.
+//
+// The pointers between values are NonNull, so the unsafe blocks below uphold the rules themselves:
+// - every pointer in the list came out of Rc::into_raw, and the list holds that Rc,
+//   so it points to a live value
+// - the pointers only change through the Cells in Link, and only with `&mut` on the list,
+//   so nothing changes under an iterator
+// - every Link knows which list it is in, so a value is in one list at a time and a list
+//   never unlinks a value from another list
+
+use std::cell::Cell;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::{PhantomData, PhantomPinned};
+use std::num::NonZeroU64;
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+type ListId = NonZeroU64;
+
+// Every list gets its own id, so a Link can tell which list it's in
+fn next_list_id() -> ListId {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NonZeroU64::new(NEXT.fetch_add(1, Ordering::Relaxed)).expect("ran out of list ids")
+}
+
+// The part of a value the list uses. It starts out in no list.
+pub struct Link<T> {
+    prev: Cell<Option<NonNull<T>>>,
+    next: Cell<Option<NonNull<T>>>,
+    list: Cell<Option<ListId>>,
+    // A value with a Link is !Unpin, so the only way to a Pin<Rc<T>> is Rc::pin
+    _pin: PhantomPinned,
+}
+
+impl<T> Link<T> {
+    pub const fn new() -> Link<T> {
+        Link {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            list: Cell::new(None),
+            _pin: PhantomPinned,
+        }
+    }
+
+    pub fn is_linked(&self) -> bool {
+        self.list.get().is_some()
+    }
+}
+
+impl<T> Default for Link<T> {
+    fn default() -> Self {
+        Link::new()
+    }
+}
+
+// A clone of a value is a new value, which isn't in any list yet
+impl<T> Clone for Link<T> {
+    fn clone(&self) -> Self {
+        Link::new()
+    }
+}
+
+impl<T> fmt::Debug for Link<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Link")
+            .field("linked", &self.is_linked())
+            .finish()
+    }
+}
+
+/// Implemented by the values that go in an IntrusiveList, to say where their Link is.
+///
+/// # Safety
+///
+/// `link` must return the same Link every time, a field of `self`, and do nothing else.
+/// The list trusts whatever it finds there.
+pub unsafe trait Linked: Sized {
+    fn link(&self) -> &Link<Self>;
+}
+
+pub struct IntrusiveList<T: Linked> {
+    head: Option<NonNull<T>>,
+    tail: Option<NonNull<T>>,
+    len: usize,
+    id: ListId,
+    // Holds an Rc of each value in it
+    _owns: PhantomData<Pin<Rc<T>>>,
+}
+
+impl<T: Linked> Default for IntrusiveList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Linked> IntrusiveList<T> {
+    pub fn new() -> IntrusiveList<T> {
+        IntrusiveList {
+            head: None,
+            tail: None,
+            len: 0,
+            id: next_list_id(),
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // SAFETY: `node` must be in this list
+    unsafe fn link_of(&self, node: NonNull<T>) -> &Link<T> {
+        unsafe { node.as_ref() }.link()
+    }
+
+    // The list's own pointer to a value, if the value is in this list.
+    // Only that pointer can be turned back into the Rc: one made from `&T`
+    // covers the value, but not the reference counts next to it.
+    fn find(&self, node: &T) -> Option<NonNull<T>> {
+        let link = node.link();
+        if link.list.get() != Some(self.id) {
+            return None;
+        }
+
+        match link.prev.get() {
+            // SAFETY: the neighbour of a value in this list is in it too
+            Some(prev) => unsafe { self.link_of(prev) }.next.get(),
+            None => self.head,
+        }
+    }
+
+    // Links a value in between two neighbours, fails and hands it back if it's already in a list
+    // SAFETY: `prev` and `next` must be next to each other in this list, None at the ends
+    unsafe fn link_between(
+        &mut self,
+        node: Pin<Rc<T>>,
+        prev: Option<NonNull<T>>,
+        next: Option<NonNull<T>>,
+    ) -> Result<(), Pin<Rc<T>>> {
+        let link = node.link();
+        if link.is_linked() {
+            return Err(node);
+        }
+        link.prev.set(prev);
+        link.next.set(next);
+        link.list.set(Some(self.id));
+
+        // The list keeps the Rc as a pointer, until `unlink` turns it back into one.
+        // SAFETY: the value stays where it is inside the Rc, the pin is only set aside,
+        // and into_raw never returns null
+        let node = unsafe {
+            let raw = Rc::into_raw(Pin::into_inner_unchecked(node));
+            NonNull::new_unchecked(raw.cast_mut())
+        };
+
+        match prev {
+            // SAFETY: both neighbours are in this list
+            Some(prev) => unsafe { self.link_of(prev) }.next.set(Some(node)),
+            None => self.head = Some(node),
+        }
+        match next {
+            // SAFETY: as above
+            Some(next) => unsafe { self.link_of(next) }.prev.set(Some(node)),
+            None => self.tail = Some(node),
+        }
+
+        self.len += 1;
+        Ok(())
+    }
+
+    // Takes a value out of the list, and gives back the Rc the list was holding
+    // SAFETY: `node` must be the list's own pointer to a value in this list
+    unsafe fn unlink(&mut self, node: NonNull<T>) -> Pin<Rc<T>> {
+        // SAFETY: the node is in this list
+        let link = unsafe { self.link_of(node) };
+        let prev = link.prev.take();
+        let next = link.next.take();
+        link.list.set(None);
+
+        match prev {
+            // SAFETY: the neighbours are in this list
+            Some(prev) => unsafe { self.link_of(prev) }.next.set(next),
+            None => self.head = next,
+        }
+        match next {
+            // SAFETY: as above
+            Some(next) => unsafe { self.link_of(next) }.prev.set(prev),
+            None => self.tail = prev,
+        }
+
+        self.len -= 1;
+        // SAFETY: the pointer came from Rc::into_raw in `link_between`, and now that
+        // it has left the list, nothing else will turn it back into an Rc
+        unsafe { Pin::new_unchecked(Rc::from_raw(node.as_ptr())) }
+    }
+
+    // Fails and hands the value back if it's already in a list, this one or another
+    pub fn push_back(&mut self, node: Pin<Rc<T>>) -> Result<(), Pin<Rc<T>>> {
+        // SAFETY: the tail and nothing after it
+        unsafe { self.link_between(node, self.tail, None) }
+    }
+
+    pub fn push_front(&mut self, node: Pin<Rc<T>>) -> Result<(), Pin<Rc<T>>> {
+        // SAFETY: nothing before the head, then the head
+        unsafe { self.link_between(node, None, self.head) }
+    }
+
+    // Inserts `node` right after `at`. Fails if `at` isn't in this list,
+    // or `node` is already in one.
+    pub fn insert_after(&mut self, at: &T, node: Pin<Rc<T>>) -> Result<(), Pin<Rc<T>>> {
+        let Some(at_node) = self.find(at) else {
+            return Err(node);
+        };
+        // SAFETY: `at` and the value after it are neighbours in this list
+        unsafe { self.link_between(node, Some(at_node), at.link().next.get()) }
+    }
+
+    pub fn insert_before(&mut self, at: &T, node: Pin<Rc<T>>) -> Result<(), Pin<Rc<T>>> {
+        let Some(at_node) = self.find(at) else {
+            return Err(node);
+        };
+        // SAFETY: the value before `at` and `at` are neighbours in this list
+        unsafe { self.link_between(node, at.link().prev.get(), Some(at_node)) }
+    }
+
+    pub fn pop_front(&mut self) -> Option<Pin<Rc<T>>> {
+        let head = self.head?;
+        // SAFETY: the head is the list's pointer to its first value
+        Some(unsafe { self.unlink(head) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<Pin<Rc<T>>> {
+        let tail = self.tail?;
+        // SAFETY: the tail is the list's pointer to its last value
+        Some(unsafe { self.unlink(tail) })
+    }
+
+    // Unlinks a value in O(1), wherever it is in the list.
+    // None if it isn't in this list, which leaves it alone.
+    pub fn remove(&mut self, node: &T) -> Option<Pin<Rc<T>>> {
+        let node = self.find(node)?;
+        // SAFETY: `find` returns the list's own pointer
+        Some(unsafe { self.unlink(node) })
+    }
+
+    // Moves a value to the back, like an LRU cache does with the entry it just used.
+    // False if it isn't in this list.
+    pub fn move_to_back(&mut self, node: &T) -> bool {
+        match self.remove(node) {
+            Some(node) => self.push_back(node).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn move_to_front(&mut self, node: &T) -> bool {
+        match self.remove(node) {
+            Some(node) => self.push_front(node).is_ok(),
+            None => false,
+        }
+    }
+
+    // O(1), the value's Link knows which list it's in
+    pub fn contains(&self, node: &T) -> bool {
+        node.link().list.get() == Some(self.id)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: the head lives as long as the borrow of self
+        self.head.map(|node| unsafe { node.as_ref() })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: the tail lives as long as the borrow of self
+        self.tail.map(|node| unsafe { node.as_ref() })
+    }
+
+    // Unlinks everything and drops the list's Rcs. A value only goes away
+    // if nobody else had an Rc to it.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Linked> Drop for IntrusiveList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Linked + fmt::Debug> fmt::Debug for IntrusiveList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+pub struct Iter<'a, T: Linked> {
+    head: Option<NonNull<T>>,
+    tail: Option<NonNull<T>>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: Linked> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: the list is borrowed for 'a, so its values stay linked and alive
+            let node = unsafe { node.as_ref() };
+            self.len -= 1;
+            self.head = node.link().next.get();
+            node
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T: Linked> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: as above
+            let node = unsafe { node.as_ref() };
+            self.len -= 1;
+            self.tail = node.link().prev.get();
+            node
+        })
+    }
+}
+
+impl<T: Linked> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T: Linked> FusedIterator for Iter<'_, T> {}
+
+impl<'a, T: Linked> IntoIterator for &'a IntrusiveList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Hands the list's Rcs back one by one
+pub struct IntoIter<T: Linked> {
+    list: IntrusiveList<T>,
+}
+
+impl<T: Linked> Iterator for IntoIter<T> {
+    type Item = Pin<Rc<T>>;
+
+    fn next(&mut self) -> Option<Pin<Rc<T>>> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T: Linked> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Pin<Rc<T>>> {
+        self.list.pop_back()
+    }
+}
+
+impl<T: Linked> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Linked> FusedIterator for IntoIter<T> {}
+
+impl<T: Linked> IntoIterator for IntrusiveList<T> {
+    type Item = Pin<Rc<T>>;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
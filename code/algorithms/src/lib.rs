@@ -1,4 +1,6 @@
 // This is the root library crate file for our project
 #![crate_name = "algorithms"]
 
+pub mod buffer;
+pub mod intrusive;
 pub mod linked_list;
@@ -0,0 +1,555 @@
+// A doubly linked list built from heap allocated nodes and raw pointers,
+// the way std::collections::LinkedList does it.
+//
+//   head                                         tail
+//    |                                            |
+//    v                                            v
+//  +------+  next  +------+  next  +------+
+//  |  1   | -----> |  2   | -----> |  3   |
+//  |      | <----- |      | <----- |      |
+//  +------+  prev  +------+  prev  +------+
+//
+// Pushing and popping at either end, and inserting or removing next to a cursor,
+// only rewires a couple of pointers, so they are O(1) however long the list is.
+//
+// Every node is allocated with Box::new and owned by the list. The pointers between
+// nodes are NonNull, which the borrow checker doesn't track, so the unsafe blocks below
+// uphold the rules themselves:
+// - a pointer in the list always points to a live node that the list owns
+// - a node is turned back into a Box exactly once, when it leaves the list
+// - `&mut` access to a value only goes through `&mut self`, so it is never aliased
+
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    // Tells the compiler we own the nodes, for drop checking and variance
+    marker: PhantomData<Box<Node<T>>>,
+}
+
+// The raw pointers opt out of Send and Sync, but the list owns its values
+// just like a Vec, so it can be sent and shared whenever T can
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let node = Self::new_node(value, None, self.head);
+        match self.head {
+            // SAFETY: head points to a live node owned by the list
+            Some(head) => unsafe { (*head.as_ptr()).prev = Some(node) },
+            None => self.tail = Some(node),
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = Self::new_node(value, self.tail, None);
+        match self.tail {
+            // SAFETY: tail points to a live node owned by the list
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        self.tail = Some(node);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: head is a live node of this list
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: tail is a live node of this list
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: the node lives as long as the borrow of self
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: &mut self guarantees nobody else is looking at the value
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: the node lives as long as the borrow of self
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: &mut self guarantees nobody else is looking at the value
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    // A cursor starting at the first element, or at the "ghost" position if the list is empty
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    // A cursor starting at the last element, or at the "ghost" position if the list is empty
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    fn new_node(value: T, prev: Link<T>, next: Link<T>) -> NonNull<Node<T>> {
+        let node = Box::new(Node { value, prev, next });
+        // Leaking the box hands the allocation over to the list,
+        // it is freed again by Box::from_raw in unlink
+        NonNull::from(Box::leak(node))
+    }
+
+    // Links a new node between `prev` and `next`, which must be neighbours in this list
+    // (or None at the ends), and returns it.
+    //
+    // SAFETY: `prev` and `next` must be live nodes of this list, next to each other
+    unsafe fn link_between(&mut self, value: T, prev: Link<T>, next: Link<T>) -> NonNull<Node<T>> {
+        let node = Self::new_node(value, prev, next);
+        match prev {
+            Some(prev) => unsafe { (*prev.as_ptr()).next = Some(node) },
+            None => self.head = Some(node),
+        }
+        match next {
+            Some(next) => unsafe { (*next.as_ptr()).prev = Some(node) },
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+        node
+    }
+
+    // Takes a node out of the list, frees it and returns its value.
+    //
+    // SAFETY: `node` must be a live node of this list
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        // Taking ownership back means this node is freed when `node` goes out of scope
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+
+        match node.prev {
+            Some(prev) => unsafe { (*prev.as_ptr()).next = node.next },
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => unsafe { (*next.as_ptr()).prev = node.prev },
+            None => self.tail = node.prev,
+        }
+        self.len -= 1;
+        node.value
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // A guard, so the remaining nodes are still freed if dropping a value panics
+        struct DropGuard<'a, T>(&'a mut LinkedList<T>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        while let Some(value) = self.pop_front() {
+            let guard = DropGuard(self);
+            drop(value);
+            std::mem::forget(guard);
+        }
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+// Iterators. Iter and IterMut walk inwards from both ends and stop when `len` runs out,
+// so next and next_back never hand out the same element twice.
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list is borrowed for 'a, so its nodes outlive the iterator
+            let node = unsafe { &*node.as_ptr() };
+            self.head = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list is borrowed for 'a, so its nodes outlive the iterator
+            let node = unsafe { &*node.as_ptr() };
+            self.tail = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+// Iterators and cursors hold raw pointers too, and are as thread safe as the references they stand for
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list is mutably borrowed for 'a and each node is handed out once
+            let node = unsafe { &mut *node.as_ptr() };
+            self.head = node.next;
+            &mut node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list is mutably borrowed for 'a and each node is handed out once
+            let node = unsafe { &mut *node.as_ptr() };
+            self.tail = node.prev;
+            &mut node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+// A position in the list that can move back and forth, and insert or remove
+// elements where it stands in O(1).
+//
+// Like std's cursors, it can also stand on a "ghost" position between the back and the
+// front of the list. Moving next from the last element, or prev from the first, lands
+// on the ghost, and moving on from the ghost wraps around to the other end.
+pub struct CursorMut<'a, T> {
+    current: Link<T>,
+    // Index of the current element, `len` when on the ghost
+    index: usize,
+    list: &'a mut LinkedList<T>,
+}
+
+unsafe impl<T: Send> Send for CursorMut<'_, T> {}
+unsafe impl<T: Sync> Sync for CursorMut<'_, T> {}
+
+impl<'a, T> CursorMut<'a, T> {
+    // None on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the cursor borrows the list mutably, the value is borrowed from the cursor
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            // SAFETY: current is a live node of the list
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).next;
+                self.index += 1;
+            },
+            // From the ghost to the front
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            // SAFETY: current is a live node of the list
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).prev;
+                // From the front this lands on the ghost, whose index is set below
+                self.index = self.index.saturating_sub(1);
+            },
+            // From the ghost to the back
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            // SAFETY: current is a live node of the list
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        // SAFETY: next is a live node, borrowed through the cursor
+        next.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            // SAFETY: current is a live node of the list
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        // SAFETY: prev is a live node, borrowed through the cursor
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    // Inserts after the current element, or at the front when on the ghost
+    pub fn insert_after(&mut self, value: T) {
+        let (prev, next) = match self.current {
+            // SAFETY: current is a live node of the list
+            Some(node) => (Some(node), unsafe { (*node.as_ptr()).next }),
+            None => (None, self.list.head),
+        };
+        // SAFETY: prev and next are neighbours in the list
+        unsafe { self.list.link_between(value, prev, next) };
+        if self.current.is_none() {
+            // The ghost stays at the end
+            self.index = self.list.len;
+        }
+    }
+
+    // Inserts before the current element, or at the back when on the ghost
+    pub fn insert_before(&mut self, value: T) {
+        let (prev, next) = match self.current {
+            // SAFETY: current is a live node of the list
+            Some(node) => (unsafe { (*node.as_ptr()).prev }, Some(node)),
+            None => (self.list.tail, None),
+        };
+        // SAFETY: prev and next are neighbours in the list
+        unsafe { self.list.link_between(value, prev, next) };
+        // Whatever we stand on moved one place back
+        self.index += 1;
+    }
+
+    // Removes the current element and moves to the next one. Does nothing on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: current is a live node, read before it is freed by unlink
+        self.current = unsafe { (*node.as_ptr()).next };
+        // SAFETY: current is a live node of the list
+        let value = unsafe { self.list.unlink(node) };
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+        Some(value)
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.list.back()
+    }
+}
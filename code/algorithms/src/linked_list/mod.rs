@@ -1,106 +1,220 @@
-#[derive(Debug, Clone)]
+// LinkedList is the real thing, LinkedListAsVec keeps its elements in a Vec
+mod doubly_linked;
+
+pub use doubly_linked::{CursorMut, IntoIter, Iter, IterMut, LinkedList};
+
+use std::ops::{Index, IndexMut};
+
+// The elements are kept in order in a Vec, so "linking" a new element in means
+// moving everything after it one place to the right.
+//
+// Comparing, ordering and hashing work element by element, like they do for Vec.
+// With the `serde` feature the list is (de)serialized as a plain sequence.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct LinkedListAsVec<T> {
     data: Vec<T>,
-    len: u32,
 }
 
-impl<T: Copy> LinkedListAsVec<T> {
+impl<T> Default for LinkedListAsVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedListAsVec<T> {
     pub fn new() -> LinkedListAsVec<T> {
-        LinkedListAsVec {
-            data: Vec::new(),
-            len: 0,
-        }
+        LinkedListAsVec { data: Vec::new() }
     }
 
     pub fn push_back(&mut self, value: T) {
         self.data.push(value);
-        self.len += 1;
     }
 
     pub fn push_front(&mut self, value: T) {
-        if self.len == 0 {
-            self.push_back(value);
-            return;
-        }
-
-        // Shift the data by 1 position
-        for i in (0..self.len).rev() {
-            let element_to_move: T = self.data[i as usize];
-            if i == self.len - 1 {
-                // for the last element allocate space and add the prev node value
-                self.data.push(element_to_move);
-            } else {
-                self.data[(i + 1) as usize] = element_to_move;
-            }
-        }
+        // Vec::insert shifts every element one position to the right to make space,
+        // with a single memmove. That is still O(n), but it moves the values instead
+        // of copying them one by one, so it works for Strings and other owned types.
+        self.data.insert(0, value);
+    }
 
-        // now make the insertion
-        self.data[0] = value;
-        self.len += 1;
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(0)
     }
 
     pub fn pop_back(&mut self) -> Option<T> {
-        if self.len > 0 {
-            self.len -= 1;
-            self.data.pop()
-        } else {
-            None
-        }
+        self.data.pop()
     }
 
-    pub fn len(&self) -> u32 {
-        self.len
+    // The Vec already knows its length, so there is no separate counter to keep in sync
+    pub fn len(&self) -> usize {
+        self.data.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.len == 0
+        self.data.is_empty()
     }
 
-    pub fn get(&self, index: u32) -> Option<&T> {
-        if self.len == 0 {
-            return None;
-        }
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.data.iter_mut()
+    }
+
+    // None for any index past the end, not just on an empty list
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.data.get(index)
+    }
 
-        Some(&self.data[index as usize])
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.data.get_mut(index)
     }
 
+    // The last element, like `back`
     pub fn peek(&self) -> Option<&T> {
-        if self.len == 0 {
+        self.back()
+    }
+
+    // The first element, like `front`
+    pub fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.data.first_mut()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.data.last()
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.data.last_mut()
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.data.contains(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    // Keeps the first `len` elements and drops the rest, does nothing if the list is shorter
+    pub fn truncate(&mut self, len: usize) {
+        self.data.truncate(len);
+    }
+
+    // Splits the list in two at `at`. The list keeps the elements before `at`
+    // and the returned list gets the rest. None if `at` is past the end.
+    pub fn split_off(&mut self, at: usize) -> Option<LinkedListAsVec<T>> {
+        if at > self.len() {
             return None;
         }
 
-        Some(&self.data[(self.len - 1) as usize])
+        Some(LinkedListAsVec {
+            data: self.data.split_off(at),
+        })
     }
 
-    // This is the main advantage of a linked list
-    pub fn insert_at_index(&mut self, index: u32, value: T) {
-        if index > self.len {
-            panic!("Index out of bounds");
+    // Moves all the elements of `other` to the back of this list, leaving `other` empty
+    pub fn append(&mut self, other: &mut LinkedListAsVec<T>) {
+        self.data.append(&mut other.data);
+    }
+
+    // This is the main advantage of a linked list.
+    // Inserting past the end fails and hands the value back.
+    pub fn insert_at_index(&mut self, index: usize, value: T) -> Result<(), T> {
+        if index > self.len() {
+            return Err(value);
         }
 
-        if index == self.len {
-            self.push_back(value);
-            return;
+        // Shifts all the elements from index to the end one position to the right,
+        // then moves the value into the gap
+        self.data.insert(index, value);
+        Ok(())
+    }
+
+    // Removes the element at `at`, None if there is no such element
+    pub fn remove(&mut self, at: usize) -> Option<T> {
+        if at >= self.len() {
+            return None;
         }
 
-        // Shift all the nodes from index to the end to make space for insertion
-        for i in (index..self.len).rev() {
-            let element_to_move: T = self.data[i as usize];
+        // Moves the element out, and everything after it one position towards the front
+        Some(self.data.remove(at))
+    }
+}
 
-            if i == self.len - 1 {
-                // for the last element allocate space and add the prev node value
-                self.data.push(element_to_move);
-            } else {
-                self.data[(i + 1) as usize] = element_to_move;
-            }
+impl<T> FromIterator<T> for LinkedListAsVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        LinkedListAsVec {
+            data: Vec::from_iter(iter),
         }
+    }
+}
+
+impl<T> Extend<T> for LinkedListAsVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.data.extend(iter);
+    }
+}
 
-        // now make the insertion
-        self.data[index as usize] = value;
-        self.len += 1;
+impl<'a, T: Copy + 'a> Extend<&'a T> for LinkedListAsVec<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.data.extend(iter);
     }
+}
+
+// The iterators are the Vec's own, there is nothing list specific about walking a Vec
+impl<T> IntoIterator for LinkedListAsVec<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedListAsVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedListAsVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter_mut()
+    }
+}
+
+// list[i] panics past the end, like it does for a Vec. Use `get` to check first.
+impl<T> Index<usize> for LinkedListAsVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.data[index]
+    }
+}
 
-    pub fn remove(&mut self, at: usize) -> T {
-        panic!("Not implemented");
+impl<T> IndexMut<usize> for LinkedListAsVec<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.data[index]
     }
 }
@@ -1,5 +1,5 @@
-use algorithms::linked_list::LinkedListAsVec;
-use std::collections::LinkedList;
+use algorithms::linked_list::{LinkedList, LinkedListAsVec};
+use std::collections::LinkedList as StdLinkedList;
 use std::time::Instant;
 
 fn main() {
@@ -13,10 +13,12 @@ fn main() {
     }
 
     // Now we will measure the time it takes to insert this data
-    // into a LinkedList vs LinkedListAsVec
+    // into std's LinkedList, our own LinkedList and LinkedListAsVec
     // We will keep inserting at the beginning of the list to bring out the worst case
+    // A single run over a handful of items is only a rough idea, `cargo bench` runs
+    // benches/lists.rs which compares the lists properly, at sizes up to a million
 
-    let mut linked_list: LinkedList<i32> = LinkedList::new();
+    let mut linked_list: StdLinkedList<i32> = StdLinkedList::new();
 
     let mut start = Instant::now();
     for value in data.iter() {
@@ -32,13 +34,29 @@ fn main() {
     );
     println!("{:?}", linked_list);
 
+    let mut our_list: LinkedList<i32> = LinkedList::new();
+
+    start = Instant::now();
+    for value in data.iter() {
+        our_list.push_front(*value);
+    }
+    end = Instant::now();
+    duration = end - start;
+
+    println!(
+        "\n\nalgorithms::LinkedList - Insertion time for {} items:{:?}",
+        our_list.len(),
+        duration
+    );
+    println!("{:?}", our_list);
+
     let mut vec_list: LinkedListAsVec<i32> = LinkedListAsVec::new();
 
     start = Instant::now();
     for value in data.iter() {
         // We could use any of these methods
         vec_list.push_front(*value);
-        //vec_list.insert_at_index(0, *value);
+        //vec_list.insert_at_index(0, *value).unwrap();
     }
     end = Instant::now();
     duration = end - start;
@@ -53,12 +71,11 @@ fn main() {
     // Now we will remove all elements from the lists
     // Validate that they come out in the same order
     for _i in 0..test_data_size {
-        assert_eq!(
-            linked_list.pop_back().unwrap(),
-            vec_list.pop_back().unwrap()
-        );
+        let expected = linked_list.pop_back().unwrap();
+        assert_eq!(expected, our_list.pop_back().unwrap());
+        assert_eq!(expected, vec_list.pop_back().unwrap());
     }
 
-    // Validate that now they are both empty
-    assert_eq!(linked_list.is_empty(), vec_list.is_empty());
+    // Validate that now they are all empty
+    assert!(linked_list.is_empty() && our_list.is_empty() && vec_list.is_empty());
 }
@@ -0,0 +1,177 @@
+// GapBuffer and RingBuffer have the same methods, so the same tests run for both
+macro_rules! list_tests {
+    ($module:ident, $list:ident) => {
+        mod $module {
+            use algorithms::buffer::$list;
+            use std::collections::VecDeque;
+            use std::rc::Rc;
+
+            fn values(list: &$list<i32>) -> Vec<i32> {
+                list.iter().copied().collect()
+            }
+
+            // Random edits, checked against VecDeque after every one.
+            // Small enough to finish under Miri, and it wraps and grows the block many times.
+            #[test]
+            fn behaves_like_vec_deque() {
+                let mut list = $list::new();
+                let mut expected = VecDeque::new();
+                let mut state: u32 = 0x9e37_79b9;
+
+                for step in 0..600 {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    let index = state as usize % (expected.len() + 2);
+
+                    match state % 6 {
+                        0 => {
+                            list.push_front(step);
+                            expected.push_front(step);
+                        }
+                        1 => {
+                            list.push_back(step);
+                            expected.push_back(step);
+                        }
+                        2 => assert_eq!(list.pop_front(), expected.pop_front()),
+                        3 => assert_eq!(list.pop_back(), expected.pop_back()),
+                        4 => {
+                            if index <= expected.len() {
+                                list.insert_at_index(index, step).unwrap();
+                                expected.insert(index, step);
+                            } else {
+                                assert_eq!(list.insert_at_index(index, step), Err(step));
+                            }
+                        }
+                        _ => assert_eq!(list.remove(index), expected.remove(index)),
+                    }
+
+                    assert_eq!(list.len(), expected.len());
+                    assert!(list.iter().eq(&expected));
+                    assert!(list.iter().rev().eq(expected.iter().rev()));
+                    assert_eq!(list.get(index), expected.get(index));
+                }
+            }
+
+            #[test]
+            fn both_ends() {
+                let mut list: $list<i32> = [1, 2, 3].into_iter().collect();
+                assert_eq!(list.peek_front(), Some(&1));
+                assert_eq!(list.peek(), Some(&3));
+
+                *list.front_mut().unwrap() = 0;
+                *list.back_mut().unwrap() = 4;
+                *list.get_mut(1).unwrap() = 20;
+                assert_eq!(values(&list), [0, 20, 4]);
+                assert_eq!((list[0], list[2]), (0, 4));
+                assert_eq!(list.get(3), None);
+                assert!(list.contains(&20) && !list.contains(&2));
+
+                for value in &mut list {
+                    *value += 1;
+                }
+                assert_eq!(values(&list), [1, 21, 5]);
+
+                list.clear();
+                assert!(list.is_empty());
+                assert_eq!((list.front(), list.back()), (None, None));
+                assert_eq!(list.pop_back(), None);
+            }
+
+            #[test]
+            fn split_off_truncate_and_append() {
+                let mut list: $list<i32> = (0..10).collect();
+                list.remove(3);
+                list.insert_at_index(1, 100).unwrap();
+
+                let mut tail = list.split_off(6).unwrap();
+                assert_eq!(values(&list), [0, 100, 1, 2, 4, 5]);
+                assert_eq!(values(&tail), [6, 7, 8, 9]);
+                assert!(list.split_off(7).is_none());
+
+                list.truncate(2);
+                list.truncate(5);
+                assert_eq!(values(&list), [0, 100]);
+
+                list.append(&mut tail);
+                assert!(tail.is_empty());
+                assert_eq!(values(&list), [0, 100, 6, 7, 8, 9]);
+
+                tail.push_front(1);
+                assert_eq!(values(&tail), [1]);
+            }
+
+            #[test]
+            fn traits_compare_elements() {
+                let mut wrapped: $list<i32> = $list::with_capacity(4);
+                wrapped.extend(&[2, 3]);
+                wrapped.push_front(1);
+                let plain: $list<i32> = vec![1, 2, 3].into_iter().collect();
+
+                assert_eq!(wrapped, plain);
+                assert_eq!(wrapped.clone(), plain);
+                assert!(plain < [1, 2, 4].into_iter().collect());
+                assert_eq!(format!("{wrapped:?}"), "[1, 2, 3]");
+                assert_eq!($list::<i32>::default(), $list::new());
+                assert_eq!(plain.into_iter().rev().collect::<Vec<_>>(), [3, 2, 1]);
+            }
+
+            // Every element is dropped exactly once, whichever way it leaves the list
+            #[test]
+            fn drops_every_element_once() {
+                let counter = Rc::new(());
+
+                let mut list: $list<Rc<()>> = (0..8).map(|_| Rc::clone(&counter)).collect();
+                list.push_front(Rc::clone(&counter));
+                list.insert_at_index(4, Rc::clone(&counter)).unwrap();
+                drop(list.remove(2));
+                drop(list.pop_back());
+                list.truncate(6);
+                let tail = list.split_off(3).unwrap();
+                assert_eq!(Rc::strong_count(&counter), 1 + 6);
+
+                let mut iter = list.into_iter();
+                drop(iter.next());
+                drop(iter.next_back());
+                assert_eq!(Rc::strong_count(&counter), 1 + 4);
+
+                drop(iter);
+                drop(tail);
+                assert_eq!(Rc::strong_count(&counter), 1);
+            }
+
+            #[cfg(feature = "serde")]
+            #[test]
+            fn serde_round_trip() {
+                let mut list: $list<i32> = (1..=3).collect();
+                list.push_front(0);
+
+                let json = serde_json::to_string(&list).unwrap();
+                assert_eq!(json, "[0,1,2,3]");
+                assert_eq!(serde_json::from_str::<$list<i32>>(&json).unwrap(), list);
+            }
+        }
+    };
+}
+
+list_tests!(gap_buffer, GapBuffer);
+list_tests!(ring_buffer, RingBuffer);
+
+// Edits next to each other stay at the gap, and moving around moves it
+#[test]
+fn gap_follows_the_edits() {
+    use algorithms::buffer::GapBuffer;
+
+    let mut text: GapBuffer<char> = "helo".chars().collect();
+    assert_eq!(text.gap(), 4);
+
+    text.insert_at_index(3, 'l').unwrap();
+    assert_eq!(text.gap(), 4);
+    text.insert_at_index(5, '!').unwrap();
+    assert_eq!(text.gap(), 6);
+    text.remove(0);
+    assert_eq!(text.gap(), 0);
+
+    assert_eq!(text.iter().collect::<String>(), "ello!");
+    assert_eq!(text.as_slices(), (&[][..], &['e', 'l', 'l', 'o', '!'][..]));
+}
@@ -0,0 +1,206 @@
+// Tests for the intrusive list, including the LRU cache it was written for
+use algorithms::intrusive::{IntrusiveList, Link, Linked};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::rc::Rc;
+
+#[derive(Debug)]
+struct Timer {
+    deadline: u64,
+    link: Link<Timer>,
+}
+
+unsafe impl Linked for Timer {
+    fn link(&self) -> &Link<Timer> {
+        &self.link
+    }
+}
+
+fn timer(deadline: u64) -> Pin<Rc<Timer>> {
+    Rc::pin(Timer {
+        deadline,
+        link: Link::new(),
+    })
+}
+
+fn deadlines(list: &IntrusiveList<Timer>) -> Vec<u64> {
+    list.iter().map(|timer| timer.deadline).collect()
+}
+
+#[test]
+fn push_and_pop_at_both_ends() {
+    let mut list = IntrusiveList::new();
+    list.push_back(timer(2)).unwrap();
+    list.push_back(timer(3)).unwrap();
+    list.push_front(timer(1)).unwrap();
+
+    assert_eq!(list.len(), 3);
+    assert_eq!(deadlines(&list), [1, 2, 3]);
+    assert_eq!(
+        list.iter().rev().map(|t| t.deadline).collect::<Vec<_>>(),
+        [3, 2, 1]
+    );
+    assert_eq!(
+        (
+            list.front().unwrap().deadline,
+            list.back().unwrap().deadline
+        ),
+        (1, 3)
+    );
+
+    let first = list.pop_front().unwrap();
+    assert_eq!(first.deadline, 1);
+    assert!(!first.link.is_linked());
+    assert_eq!(list.pop_back().unwrap().deadline, 3);
+    assert_eq!(list.pop_back().unwrap().deadline, 2);
+    assert!(list.pop_front().is_none() && list.is_empty());
+}
+
+#[test]
+fn remove_by_reference() {
+    let timers: Vec<_> = (1..=4).map(timer).collect();
+    let mut list = IntrusiveList::new();
+    for timer in &timers {
+        list.push_back(timer.clone()).unwrap();
+    }
+
+    // From the middle, the front and the back, each without walking the list
+    assert_eq!(list.remove(&timers[1]).unwrap().deadline, 2);
+    assert_eq!(list.remove(&timers[0]).unwrap().deadline, 1);
+    assert_eq!(list.remove(&timers[3]).unwrap().deadline, 4);
+    assert_eq!(deadlines(&list), [3]);
+
+    // A value that isn't there is left alone
+    assert!(list.remove(&timers[1]).is_none());
+    assert!(!list.contains(&timers[1]) && list.contains(&timers[2]));
+}
+
+#[test]
+fn a_value_is_in_one_list_at_a_time() {
+    let shared = timer(7);
+    let mut first = IntrusiveList::new();
+    let mut second = IntrusiveList::new();
+
+    first.push_back(shared.clone()).unwrap();
+    assert!(second.push_back(shared.clone()).is_err());
+    assert!(first.push_front(shared.clone()).is_err());
+
+    // Removing it from the wrong list does nothing to the right one
+    assert!(second.remove(&shared).is_none());
+    assert!(!second.move_to_front(&shared));
+    assert_eq!(deadlines(&first), [7]);
+
+    first.remove(&shared).unwrap();
+    second.push_back(shared.clone()).unwrap();
+    assert!(second.contains(&shared) && !first.contains(&shared));
+}
+
+#[test]
+fn insert_next_to_a_value() {
+    let mut wheel = IntrusiveList::new();
+    let ten = timer(10);
+    let thirty = timer(30);
+    wheel.push_back(ten.clone()).unwrap();
+    wheel.push_back(thirty.clone()).unwrap();
+
+    wheel.insert_before(&ten, timer(5)).unwrap();
+    wheel.insert_after(&ten, timer(20)).unwrap();
+    wheel.insert_after(&thirty, timer(40)).unwrap();
+    assert_eq!(deadlines(&wheel), [5, 10, 20, 30, 40]);
+
+    // Next to a value that isn't in the list, or with one that already is
+    assert!(wheel.insert_after(&timer(15), timer(16)).is_err());
+    assert!(wheel.insert_before(&thirty, ten.clone()).is_err());
+    assert_eq!(wheel.len(), 5);
+}
+
+// The LRU cache the list is made for: a map finds an entry by key,
+// the list keeps the entries from least to most recently used
+#[test]
+fn lru_cache() {
+    #[derive(Debug)]
+    struct Entry {
+        key: u32,
+        link: Link<Entry>,
+    }
+
+    unsafe impl Linked for Entry {
+        fn link(&self) -> &Link<Entry> {
+            &self.link
+        }
+    }
+
+    let mut map: HashMap<u32, Pin<Rc<Entry>>> = HashMap::new();
+    let mut order: IntrusiveList<Entry> = IntrusiveList::new();
+    let capacity = 3;
+
+    for key in [1, 2, 3, 1, 4, 2, 5, 1] {
+        if let Some(entry) = map.get(&key) {
+            assert!(order.move_to_back(entry));
+            continue;
+        }
+        if map.len() == capacity {
+            let oldest = order.pop_front().unwrap();
+            map.remove(&oldest.key);
+        }
+        let entry = Rc::pin(Entry {
+            key,
+            link: Link::new(),
+        });
+        order.push_back(entry.clone()).unwrap();
+        map.insert(key, entry);
+    }
+
+    let keys: Vec<u32> = order.iter().map(|entry| entry.key).collect();
+    assert_eq!(keys, [2, 5, 1]);
+    assert_eq!(map.len(), 3);
+}
+
+// The list only drops its own Rc, a value goes away when nobody else has one either
+#[test]
+fn dropping_the_list_lets_go_of_its_values() {
+    #[derive(Debug)]
+    struct Counted {
+        drops: Rc<Cell<u32>>,
+        link: Link<Counted>,
+    }
+
+    unsafe impl Linked for Counted {
+        fn link(&self) -> &Link<Counted> {
+            &self.link
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let counted = || {
+        Rc::pin(Counted {
+            drops: drops.clone(),
+            link: Link::new(),
+        })
+    };
+
+    let kept = counted();
+    let mut list = IntrusiveList::new();
+    list.push_back(kept.clone()).unwrap();
+    list.push_back(counted()).unwrap();
+    list.push_back(counted()).unwrap();
+    assert_eq!(drops.get(), 0);
+
+    let mut values = list.into_iter();
+    drop(values.next_back());
+    assert_eq!(drops.get(), 1);
+
+    drop(values);
+    assert_eq!(drops.get(), 2);
+    assert!(!kept.link.is_linked());
+
+    drop(kept);
+    assert_eq!(drops.get(), 3);
+}
@@ -0,0 +1,141 @@
+// Tests for the doubly linked list, its iterators and its cursor
+use algorithms::linked_list::LinkedList;
+use std::rc::Rc;
+
+#[test]
+fn push_and_pop_at_both_ends() {
+    let mut list = LinkedList::new();
+    assert_eq!(list.pop_front(), None);
+    assert_eq!(list.pop_back(), None);
+
+    list.push_back(2);
+    list.push_back(3);
+    list.push_front(1);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.front(), Some(&1));
+    assert_eq!(list.back(), Some(&3));
+
+    *list.front_mut().unwrap() = 10;
+    *list.back_mut().unwrap() = 30;
+    assert_eq!(list.pop_front(), Some(10));
+    assert_eq!(list.pop_back(), Some(30));
+    assert_eq!(list.pop_back(), Some(2));
+    assert!(list.is_empty());
+    assert_eq!(list.front(), None);
+    assert_eq!(list.back(), None);
+}
+
+#[test]
+fn iterators_meet_in_the_middle() {
+    let mut list: LinkedList<i32> = (1..=5).collect();
+
+    let mut iter = list.iter();
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.next(), Some(&1));
+    assert_eq!(iter.next_back(), Some(&5));
+    assert_eq!(iter.next(), Some(&2));
+    assert_eq!(iter.next_back(), Some(&4));
+    assert_eq!(iter.next(), Some(&3));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    for value in &mut list {
+        *value *= 10;
+    }
+    let backwards: Vec<i32> = list.iter().rev().copied().collect();
+    assert_eq!(backwards, [50, 40, 30, 20, 10]);
+
+    let mut into_iter = list.into_iter();
+    assert_eq!(into_iter.next_back(), Some(50));
+    assert_eq!(into_iter.collect::<Vec<_>>(), [10, 20, 30, 40]);
+}
+
+#[test]
+fn cursor_inserts_and_removes_in_the_middle() {
+    let mut list: LinkedList<String> = ["a", "c", "e"].iter().map(|s| s.to_string()).collect();
+
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    assert_eq!(cursor.current().map(|s| s.as_str()), Some("c"));
+    assert_eq!(cursor.index(), Some(1));
+
+    cursor.insert_before("b".to_string());
+    cursor.insert_after("d".to_string());
+    assert_eq!(cursor.index(), Some(2));
+    assert_eq!(cursor.peek_prev().map(|s| s.as_str()), Some("b"));
+    assert_eq!(cursor.peek_next().map(|s| s.as_str()), Some("d"));
+
+    assert_eq!(cursor.remove_current().as_deref(), Some("c"));
+    assert_eq!(cursor.current().map(|s| s.as_str()), Some("d"));
+
+    // Past the back is the ghost position, then it wraps around to the front
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.index(), None);
+    assert_eq!(cursor.remove_current(), None);
+    cursor.insert_before("f".to_string());
+    cursor.move_next();
+    assert_eq!(cursor.current().map(|s| s.as_str()), Some("a"));
+    cursor.move_prev();
+    cursor.move_prev();
+    assert_eq!(cursor.current().map(|s| s.as_str()), Some("f"));
+    assert_eq!(cursor.index(), Some(4));
+
+    let values: Vec<&str> = list.iter().map(|s| s.as_str()).collect();
+    assert_eq!(values, ["a", "b", "d", "e", "f"]);
+}
+
+#[test]
+fn cursor_on_an_empty_list() {
+    let mut list = LinkedList::new();
+    let mut cursor = list.cursor_back_mut();
+    assert_eq!(cursor.current(), None);
+    cursor.insert_after(2);
+    cursor.insert_after(1);
+    cursor.insert_before(3);
+    assert_eq!(cursor.index(), None);
+    assert_eq!(cursor.front(), Some(&1));
+    assert_eq!(cursor.back(), Some(&3));
+
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+}
+
+#[test]
+fn every_value_is_dropped_once() {
+    let counter = Rc::new(());
+
+    let mut list = LinkedList::new();
+    for _ in 0..10 {
+        list.push_back(Rc::clone(&counter));
+    }
+    assert_eq!(Rc::strong_count(&counter), 11);
+
+    drop(list.pop_front());
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    drop(cursor.remove_current());
+    assert_eq!(Rc::strong_count(&counter), 9);
+
+    // A partly used IntoIter still drops what is left
+    let mut into_iter = list.clone().into_iter();
+    into_iter.next();
+    drop(into_iter);
+    assert_eq!(Rc::strong_count(&counter), 9);
+
+    drop(list);
+    assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+fn clone_and_compare() {
+    let list: LinkedList<i32> = (0..4).collect();
+    let mut copy = list.clone();
+    assert_eq!(list, copy);
+    assert_eq!(format!("{:?}", copy), "[0, 1, 2, 3]");
+
+    copy.push_back(4);
+    assert_ne!(list, copy);
+    copy.clear();
+    assert!(copy.is_empty());
+    assert_eq!(copy, LinkedList::default());
+}
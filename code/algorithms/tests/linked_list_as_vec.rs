@@ -0,0 +1,174 @@
+use algorithms::linked_list::LinkedListAsVec;
+
+fn list(values: &[i32]) -> LinkedListAsVec<i32> {
+    values.iter().copied().collect()
+}
+
+fn values(list: &LinkedListAsVec<i32>) -> Vec<i32> {
+    list.iter().copied().collect()
+}
+
+#[test]
+fn get_is_bounds_checked() {
+    let mut list = list(&[1, 2, 3]);
+    assert_eq!(list.get(2), Some(&3));
+    assert_eq!(list.get(3), None);
+    assert_eq!(list.get(usize::MAX), None);
+
+    *list.get_mut(0).unwrap() = 10;
+    assert_eq!(list.get_mut(3), None);
+    assert_eq!(values(&list), [10, 2, 3]);
+    assert_eq!(LinkedListAsVec::<i32>::new().get(0), None);
+}
+
+#[test]
+fn both_ends() {
+    let mut list = list(&[1, 2, 3]);
+    assert_eq!(list.peek_front(), Some(&1));
+    assert_eq!(list.peek(), Some(&3));
+
+    *list.front_mut().unwrap() = 0;
+    *list.back_mut().unwrap() = 4;
+    assert_eq!((list.front(), list.back()), (Some(&0), Some(&4)));
+
+    assert_eq!(list.pop_front(), Some(0));
+    assert_eq!(list.pop_back(), Some(4));
+    assert_eq!(list.pop_front(), Some(2));
+    assert_eq!(list.pop_front(), None);
+    assert_eq!((list.peek_front(), list.peek()), (None, None));
+    assert!(list.is_empty());
+}
+
+#[test]
+fn insert_and_remove_by_index() {
+    let mut list = list(&[1, 3]);
+    assert_eq!(list.insert_at_index(1, 2), Ok(()));
+    assert_eq!(list.insert_at_index(3, 4), Ok(()));
+    assert_eq!(list.insert_at_index(9, 9), Err(9));
+    assert_eq!(values(&list), [1, 2, 3, 4]);
+
+    assert_eq!(list.remove(1), Some(2));
+    assert_eq!(list.remove(2), Some(4));
+    assert_eq!(list.remove(2), None);
+    assert_eq!(values(&list), [1, 3]);
+    assert_eq!(list.len(), 2);
+}
+
+#[test]
+fn contains_clear_and_truncate() {
+    let mut list = list(&[1, 2, 3, 4]);
+    assert!(list.contains(&3));
+    assert!(!list.contains(&5));
+
+    list.truncate(10);
+    assert_eq!(list.len(), 4);
+    list.truncate(2);
+    assert_eq!(values(&list), [1, 2]);
+
+    list.clear();
+    assert!(list.is_empty());
+    assert!(!list.contains(&1));
+}
+
+#[test]
+fn split_off_and_append() {
+    let mut list = list(&[1, 2, 3, 4]);
+    assert!(list.split_off(5).is_none());
+
+    let mut tail = list.split_off(1).unwrap();
+    assert_eq!(values(&list), [1]);
+    assert_eq!(values(&tail), [2, 3, 4]);
+    assert_eq!(tail.len(), 3);
+
+    let empty = tail.split_off(3).unwrap();
+    assert!(empty.is_empty());
+
+    list.append(&mut tail);
+    assert_eq!(values(&list), [1, 2, 3, 4]);
+    assert_eq!(list.len(), 4);
+    assert!(tail.is_empty());
+}
+
+#[test]
+fn holds_owned_values() {
+    #[derive(Debug, PartialEq)]
+    struct Record {
+        name: String,
+        tags: Vec<String>,
+    }
+
+    let record = |name: &str| Record {
+        name: name.to_string(),
+        tags: vec![name.to_uppercase()],
+    };
+
+    let mut list = LinkedListAsVec::new();
+    list.push_back(record("b"));
+    list.push_front(record("a"));
+    list.insert_at_index(2, record("d")).unwrap();
+    list.insert_at_index(2, record("c")).unwrap();
+    assert!(list.insert_at_index(5, record("x")).is_err());
+
+    assert_eq!(list.len(), 4);
+    assert_eq!(list.remove(1), Some(record("b")));
+    assert_eq!(list.pop_front().unwrap().name, "a");
+    assert_eq!(list.peek().unwrap().tags, ["D"]);
+    assert!(list.contains(&record("c")));
+}
+
+#[test]
+fn works_with_iterator_pipelines() {
+    let mut list: LinkedListAsVec<i32> = (1..=3).collect();
+    list.extend([4, 5]);
+    list.extend(&[6]);
+
+    for value in &mut list {
+        *value *= 2;
+    }
+    let sum: i32 = (&list).into_iter().sum();
+    assert_eq!(sum, 42);
+
+    list[0] = 1;
+    assert_eq!(list[0], 1);
+    assert_eq!(list.iter().next_back(), Some(&12));
+
+    let odd: LinkedListAsVec<i32> = list.into_iter().filter(|v| v % 2 == 1).collect();
+    assert_eq!(odd.len(), 1);
+    assert_eq!(odd.front(), Some(&1));
+}
+
+#[test]
+#[should_panic]
+fn indexing_past_the_end_panics() {
+    let list = list(&[1]);
+    let _ = list[1];
+}
+
+#[test]
+fn compares_orders_and_hashes_like_a_vec() {
+    use std::collections::HashSet;
+
+    assert_eq!(LinkedListAsVec::<i32>::default(), LinkedListAsVec::new());
+    assert!(list(&[1, 2]) < list(&[1, 3]));
+    assert!(list(&[1, 2]) < list(&[1, 2, 0]));
+
+    let mut lists = vec![list(&[2]), list(&[1, 5]), list(&[])];
+    lists.sort();
+    assert_eq!(lists, [list(&[]), list(&[1, 5]), list(&[2])]);
+
+    let set: HashSet<LinkedListAsVec<i32>> = [list(&[1, 2]), list(&[1, 2]), list(&[2, 1])]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes_as_a_sequence() {
+    let list: LinkedListAsVec<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+    let json = serde_json::to_string(&list).unwrap();
+    assert_eq!(json, r#"["a","b"]"#);
+
+    let back: LinkedListAsVec<String> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, list);
+}
@@ -0,0 +1,6 @@
+-- AUTOINCREMENT makes sure the id of a deleted person is never handed out again
+CREATE TABLE people (
+    id    INTEGER PRIMARY KEY AUTOINCREMENT,
+    name  TEXT NOT NULL,
+    email TEXT NOT NULL
+);
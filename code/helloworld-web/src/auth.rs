@@ -0,0 +1,273 @@
+/*
+    Bearer token authentication with JSON Web Tokens.
+
+    POST /login with a test user's credentials returns a token:
+
+        curl -H "Content-Type: application/json" -d '{"username":"bbc","password":"bbc-password"}' localhost:8080/login
+
+    which is then sent with every request to a protected route:
+
+        curl -H "Authorization: Bearer <token>" -d hello localhost:8080/echo
+
+    Missing, malformed and expired tokens get 401, valid tokens without the needed role get 403.
+*/
+
+use crate::problem::{ApiError, Problem};
+use crate::settings::Settings;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{post, web, HttpMessage, ResponseError};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    // Can change data
+    Editor,
+    // Can only use the routes that need a login, like echo
+    Viewer,
+}
+
+// Handlers behind the middleware can take `web::ReqData<Claims>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub role: Role,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+// Users for trying out the API, a real service would look these up in a database
+const TEST_USERS: [(&str, &str, Role); 2] = [
+    ("bbc", "bbc-password", Role::Editor),
+    ("guest", "guest-password", Role::Viewer),
+];
+
+pub struct JwtAuth {
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    hs256_key: DecodingKey,
+    rs256_key: Option<DecodingKey>,
+    ttl: Duration,
+}
+
+impl JwtAuth {
+    // Issues and accepts HS256 tokens signed with `secret`
+    pub fn hs256(secret: &[u8]) -> Self {
+        JwtAuth {
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret),
+            hs256_key: DecodingKey::from_secret(secret),
+            rs256_key: None,
+            ttl: Duration::from_secs(60 * 60),
+        }
+    }
+
+    // Also accepts RS256 tokens signed by the private half of `public_pem`
+    pub fn accept_rs256(mut self, public_pem: &[u8]) -> jsonwebtoken::errors::Result<Self> {
+        self.rs256_key = Some(DecodingKey::from_rsa_pem(public_pem)?);
+        Ok(self)
+    }
+
+    // Signs the tokens issued by /login with RS256 instead of HS256
+    pub fn issue_rs256(mut self, private_pem: &[u8]) -> jsonwebtoken::errors::Result<Self> {
+        self.algorithm = Algorithm::RS256;
+        self.encoding_key = EncodingKey::from_rsa_pem(private_pem)?;
+        Ok(self)
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn from_settings(settings: &Settings) -> std::io::Result<Self> {
+        let secret = match &settings.jwt_secret {
+            Some(secret) => secret.clone(),
+            None => {
+                tracing::warn!("no jwt_secret set, tokens will not survive a restart");
+                // Two v4 UUIDs, 244 bits from the OS random number generator
+                format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+            }
+        };
+        let mut auth = JwtAuth::hs256(secret.as_bytes()).ttl(Duration::from_secs(settings.jwt_ttl));
+
+        if let Some(path) = &settings.jwt_rsa_public_key {
+            auth = auth
+                .accept_rs256(&std::fs::read(path)?)
+                .map_err(std::io::Error::other)?;
+        }
+        if let Some(path) = &settings.jwt_rsa_private_key {
+            if auth.rs256_key.is_none() {
+                return Err(std::io::Error::other(
+                    "jwt_rsa_private_key needs jwt_rsa_public_key, to verify the tokens it signs",
+                ));
+            }
+            auth = auth
+                .issue_rs256(&std::fs::read(path)?)
+                .map_err(std::io::Error::other)?;
+        }
+        Ok(auth)
+    }
+
+    pub fn issue(&self, username: &str, role: Role) -> jsonwebtoken::errors::Result<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let claims = Claims {
+            sub: username.to_string(),
+            role,
+            iat: now,
+            exp: now + self.ttl.as_secs(),
+        };
+
+        jsonwebtoken::encode(&Header::new(self.algorithm), &claims, &self.encoding_key)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims, ApiError> {
+        let invalid = |e: jsonwebtoken::errors::Error| {
+            ApiError::Unauthorized(format!("Invalid token: {}", e))
+        };
+
+        // The key is picked by the algorithm in the token's header, but each key only
+        // works with its own algorithm, so an RS256 public key can never be used as an
+        // HS256 secret
+        let header = jsonwebtoken::decode_header(token).map_err(invalid)?;
+        let key = match header.alg {
+            Algorithm::HS256 => &self.hs256_key,
+            Algorithm::RS256 => self.rs256_key.as_ref().ok_or_else(|| {
+                ApiError::Unauthorized("RS256 tokens are not accepted".to_string())
+            })?,
+            alg => {
+                return Err(ApiError::Unauthorized(format!(
+                    "{:?} tokens are not accepted",
+                    alg
+                )))
+            }
+        };
+
+        let validation = Validation::new(header.alg);
+        let data = jsonwebtoken::decode::<Claims>(token, key, &validation).map_err(invalid)?;
+        Ok(data.claims)
+    }
+}
+
+fn authenticate(req: &ServiceRequest) -> Result<Claims, ApiError> {
+    let auth = req
+        .app_data::<web::Data<JwtAuth>>()
+        .ok_or_else(|| ApiError::Internal("JwtAuth is missing from app_data".to_string()))?;
+
+    let header = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+
+    // The scheme is case-insensitive, "bearer abc" is as good as "Bearer abc"
+    let token = match header.split_once(' ') {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("Bearer") => token.trim(),
+        _ => {
+            return Err(ApiError::Unauthorized(
+                "Expected a Bearer token".to_string(),
+            ))
+        }
+    };
+
+    auth.verify(token)
+}
+
+// Middleware for routes that need any logged in user:
+//
+//     #[post("/echo", wrap = "from_fn(crate::auth::require_auth)")]
+pub async fn require_auth<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    match authenticate(&req) {
+        Ok(claims) => pass(req, claims, next).await,
+        Err(e) => Ok(reject(req, e)),
+    }
+}
+
+// Middleware for routes that change data
+pub async fn require_editor<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    match authenticate(&req) {
+        Ok(claims) if claims.role == Role::Editor => pass(req, claims, next).await,
+        Ok(claims) => Ok(reject(
+            req,
+            ApiError::Forbidden(format!("{} is not an editor", claims.sub)),
+        )),
+        Err(e) => Ok(reject(req, e)),
+    }
+}
+
+async fn pass<B: MessageBody>(
+    req: ServiceRequest,
+    claims: Claims,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    req.extensions_mut().insert(claims);
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+// Answer with the problem response here instead of returning an Err, so the handler
+// never runs and the outer middlewares see a normal 401/403 response
+fn reject<B>(req: ServiceRequest, error: ApiError) -> ServiceResponse<EitherBody<B>> {
+    req.into_response(error.error_response())
+        .map_into_right_body()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Token {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+}
+
+#[utoipa::path(
+    tag = "auth",
+    request_body = Credentials,
+    responses(
+        (status = 200, description = "A token for the user", body = Token),
+        (status = 401, description = "Wrong username or password", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+#[post("/login")]
+pub async fn login(
+    auth: web::Data<JwtAuth>,
+    credentials: web::Json<Credentials>,
+) -> Result<web::Json<Token>, ApiError> {
+    let role = TEST_USERS
+        .iter()
+        .find(|(username, password, _)| {
+            *username == credentials.username && *password == credentials.password
+        })
+        .map(|(_, _, role)| *role)
+        .ok_or_else(|| ApiError::Unauthorized("Wrong username or password".to_string()))?;
+
+    let access_token = auth
+        .issue(&credentials.username, role)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    tracing::info!(user = %credentials.username, "issued token");
+
+    Ok(web::Json(Token {
+        access_token,
+        token_type: String::from("Bearer"),
+        expires_in: auth.ttl.as_secs(),
+    }))
+}
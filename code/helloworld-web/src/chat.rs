@@ -0,0 +1,232 @@
+/*
+    A chat server on WebSockets, as a reference for stateful realtime services.
+
+    GET /ws?room=lobby&name=bbc upgrades the connection to a WebSocket. Text sent by a
+    client is broadcast, as JSON, to everyone in the same room:
+
+        {"kind":"message","room":"lobby","from":"bbc","text":"hello"}
+
+    along with "join" and "leave" messages as people come and go.
+
+    The ChatHub is the only shared state, every connection gets its own task that
+    relays between the socket and the hub. The server pings each client every few
+    seconds and drops the ones that stop answering, so dead connections don't stay
+    in their rooms forever.
+*/
+
+use crate::problem::{ApiError, Problem};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_ws::{AggregatedMessage, CloseReason, Session};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::Instrument;
+use utoipa::{IntoParams, ToSchema};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+// A client that hasn't sent anything, not even a pong, for this long is gone
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_MESSAGE_SIZE: usize = 4 * 1024;
+// Messages queued for a client that reads too slowly, newer ones are dropped
+const QUEUE_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Join,
+    Message,
+    Leave,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ChatMessage {
+    pub kind: Kind,
+    pub room: String,
+    pub from: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+}
+
+type Members = HashMap<u64, mpsc::Sender<String>>;
+
+// Rooms and the connections in them, shared by all the workers
+#[derive(Debug, Default)]
+pub struct ChatHub {
+    rooms: Mutex<HashMap<String, Members>>,
+    next_id: AtomicU64,
+}
+
+impl ChatHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a connection to `room`, it receives the room's messages on the returned channel
+    pub fn join(&self, room: &str) -> (u64, mpsc::Receiver<String>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel(QUEUE_SIZE);
+
+        let mut rooms = self.rooms.lock().unwrap();
+        rooms.entry(room.to_string()).or_default().insert(id, tx);
+        (id, rx)
+    }
+
+    pub fn leave(&self, room: &str, id: u64) {
+        let mut rooms = self.rooms.lock().unwrap();
+        if let Some(members) = rooms.get_mut(room) {
+            members.remove(&id);
+            // Rooms only exist while someone is in them
+            if members.is_empty() {
+                rooms.remove(room);
+            }
+        }
+    }
+
+    pub fn broadcast(&self, message: &ChatMessage) {
+        let json = match serde_json::to_string(message) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!(error = %e, "failed to serialize chat message");
+                return;
+            }
+        };
+
+        let rooms = self.rooms.lock().unwrap();
+        for (id, tx) in rooms.get(&message.room).into_iter().flatten() {
+            // Never wait here, one slow client must not hold up the whole room
+            if let Err(mpsc::error::TrySendError::Full(_)) = tx.try_send(json.clone()) {
+                tracing::warn!(room = %message.room, id, "client is too slow, dropped a message");
+            }
+        }
+    }
+
+    pub fn members(&self, room: &str) -> usize {
+        let rooms = self.rooms.lock().unwrap();
+        rooms.get(room).map_or(0, |members| members.len())
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct JoinQuery {
+    /// Room to join [default: lobby]
+    pub room: Option<String>,
+    /// Name shown to the others [default: anonymous]
+    pub name: Option<String>,
+}
+
+fn checked(value: Option<String>, default: &str, field: &str) -> Result<String, ApiError> {
+    let value = value.unwrap_or_else(|| default.to_string());
+    if value.trim().is_empty() || value.chars().count() > 32 {
+        return Err(ApiError::BadRequest(format!(
+            "{} must have between 1 and 32 characters",
+            field
+        )));
+    }
+    Ok(value)
+}
+
+#[utoipa::path(
+    tag = "chat",
+    params(JoinQuery),
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol, messages are ChatMessage JSON", body = ChatMessage),
+        (status = 400, description = "Not a WebSocket handshake, or a bad room or name", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+#[get("/ws")]
+pub async fn ws(
+    req: HttpRequest,
+    body: web::Payload,
+    hub: web::Data<ChatHub>,
+    query: web::Query<JoinQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let query = query.into_inner();
+    let room = checked(query.room, "lobby", "room")?;
+    let name = checked(query.name, "anonymous", "name")?;
+
+    let (response, session, stream) = actix_ws::handle(&req, body)?;
+    let stream = stream
+        .max_frame_size(MAX_MESSAGE_SIZE)
+        .aggregate_continuations()
+        .max_continuation_size(MAX_MESSAGE_SIZE);
+
+    // The connection outlives this handler, it runs on its own task from here on
+    let span = tracing::info_span!("chat", %room, %name);
+    actix_web::rt::spawn(
+        run_session(hub.into_inner(), room, name, session, stream).instrument(span),
+    );
+    Ok(response)
+}
+
+async fn run_session(
+    hub: std::sync::Arc<ChatHub>,
+    room: String,
+    name: String,
+    mut session: Session,
+    mut stream: actix_ws::AggregatedMessageStream,
+) {
+    let message = |kind, text: String| ChatMessage {
+        kind,
+        room: room.clone(),
+        from: name.clone(),
+        text,
+    };
+
+    let (id, mut outgoing) = hub.join(&room);
+    tracing::info!(members = hub.members(&room), "joined");
+    hub.broadcast(&message(Kind::Join, String::new()));
+
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut last_seen = Instant::now();
+
+    let reason: Option<CloseReason> = loop {
+        tokio::select! {
+            incoming = stream.recv() => {
+                last_seen = Instant::now();
+                match incoming {
+                    Some(Ok(AggregatedMessage::Text(text))) => {
+                        hub.broadcast(&message(Kind::Message, text.to_string()));
+                    }
+                    Some(Ok(AggregatedMessage::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break None;
+                        }
+                    }
+                    Some(Ok(AggregatedMessage::Pong(_))) => {}
+                    // This is a text chat
+                    Some(Ok(AggregatedMessage::Binary(_))) => {}
+                    Some(Ok(AggregatedMessage::Close(reason))) => break reason,
+                    Some(Err(e)) => {
+                        tracing::warn!(error = %e, "protocol error");
+                        break None;
+                    }
+                    None => break None,
+                }
+            }
+            Some(json) = outgoing.recv() => {
+                if session.text(json).await.is_err() {
+                    break None;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > CLIENT_TIMEOUT {
+                    tracing::info!("client timed out");
+                    break None;
+                }
+                if session.ping(b"").await.is_err() {
+                    break None;
+                }
+            }
+        }
+    };
+
+    // Runs however the connection ended, so nobody lingers in the room
+    hub.leave(&room, id);
+    hub.broadcast(&message(Kind::Leave, String::new()));
+    tracing::info!(members = hub.members(&room), "left");
+    let _ = session.close(reason).await;
+}
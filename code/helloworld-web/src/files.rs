@@ -0,0 +1,170 @@
+/*
+    File uploads and downloads, streamed so a large file never has to fit in memory.
+
+    POST /upload         the raw request body is written to `upload_dir`, chunk by chunk,
+                         hashing it on the way. Bodies over `upload_limit` bytes get 413.
+    GET  /files/{id}     the file, served by actix-files with Range, ETag and Last-Modified support
+
+        curl -H "Authorization: Bearer $TOKEN" --data-binary @big.iso localhost:8080/upload
+        curl -r 0-1023 localhost:8080/files/<id>
+*/
+
+use crate::problem::{ApiError, Problem};
+use actix_files::NamedFile;
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::middleware::from_fn;
+use actix_web::{get, post, web, HttpResponse};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+// Where uploads go and how big they can be, from the settings
+#[derive(Debug, Clone)]
+pub struct Uploads {
+    pub dir: PathBuf,
+    pub limit: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Upload {
+    pub id: String,
+    pub size: u64,
+    // Hex encoded SHA-256 of the contents, for the client to check the upload arrived intact
+    pub sha256: String,
+}
+
+fn too_large(limit: u64) -> ApiError {
+    ApiError::PayloadTooLarge(format!("Uploads are limited to {} bytes", limit))
+}
+
+fn io_error(e: std::io::Error) -> ApiError {
+    ApiError::Internal(e.to_string())
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+// Streams the body into `file`, returning its size and hash, or an error once it gets too big
+async fn write_body(
+    payload: &mut web::Payload,
+    file: &mut tokio::fs::File,
+    limit: u64,
+) -> Result<(u64, String), ApiError> {
+    let mut hasher = Sha256::new();
+    let mut size = 0;
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        size += chunk.len() as u64;
+        if size > limit {
+            return Err(too_large(limit));
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk).await.map_err(io_error)?;
+    }
+    file.flush().await.map_err(io_error)?;
+
+    let sha256 = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok((size, sha256))
+}
+
+#[utoipa::path(
+    tag = "files",
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 201, description = "Stored, its URL is in the Location header", body = Upload),
+        (status = 401, description = "Missing or invalid token", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "Larger than upload_limit", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer" = []))
+)]
+#[post("/upload", wrap = "from_fn(crate::auth::require_auth)")]
+pub async fn upload(
+    uploads: web::Data<Uploads>,
+    req: actix_web::HttpRequest,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    // Refuse early when the client tells us the size, the stream is checked either way
+    if content_length(req.headers()).is_some_and(|length| length > uploads.limit) {
+        return Err(too_large(uploads.limit));
+    }
+
+    tokio::fs::create_dir_all(&uploads.dir)
+        .await
+        .map_err(io_error)?;
+
+    // Written under a temporary name, so a half-written file can never be downloaded
+    let id = Uuid::new_v4().to_string();
+    let path = uploads.dir.join(&id);
+    let partial = uploads.dir.join(format!("{}.part", id));
+
+    let mut file = tokio::fs::File::create(&partial).await.map_err(io_error)?;
+    let written = write_body(&mut payload, &mut file, uploads.limit).await;
+    drop(file);
+    let stored = match written {
+        Ok(written) => tokio::fs::rename(&partial, &path)
+            .await
+            .map(|()| written)
+            .map_err(io_error),
+        Err(e) => Err(e),
+    };
+    // Whether writing or renaming failed, nobody is going to finish the partial file
+    let (size, sha256) = match stored {
+        Ok(stored) => stored,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+    };
+
+    tracing::info!(%id, size, %sha256, "stored upload");
+    Ok(HttpResponse::Created()
+        .insert_header(("Location", format!("/files/{}", id)))
+        .json(Upload { id, size, sha256 }))
+}
+
+// Only ids we could have generated, which also keeps `..` and `/` out of the path
+fn file_path(dir: &Path, id: &str) -> Option<PathBuf> {
+    let id = Uuid::parse_str(id).ok()?;
+    Some(dir.join(id.hyphenated().to_string()))
+}
+
+#[utoipa::path(
+    tag = "files",
+    params(("id" = String, Path, description = "Id returned by /upload")),
+    responses(
+        (status = 200, description = "The file", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 206, description = "The requested byte range of the file", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 404, description = "No file with that id", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+#[get("/files/{id}")]
+pub async fn download(
+    uploads: web::Data<Uploads>,
+    id: web::Path<String>,
+) -> Result<NamedFile, ApiError> {
+    let not_found = || ApiError::NotFound(format!("No file with id {}", id));
+    let path = file_path(&uploads.dir, &id).ok_or_else(not_found)?;
+
+    let file = NamedFile::open_async(&path)
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => not_found(),
+            _ => io_error(e),
+        })?;
+    Ok(file.use_etag(true).use_last_modified(true))
+}
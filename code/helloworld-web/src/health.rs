@@ -0,0 +1,122 @@
+/*
+    Probes for load balancers and orchestrators like Kubernetes:
+
+    GET /healthz    the process is up and serving requests (liveness)
+    GET /readyz     it should get traffic: not shutting down, and the database answers (readiness)
+
+    On SIGTERM (or Ctrl-C) the server doesn't stop right away. /readyz starts failing first,
+    and only after `shutdown_delay` seconds, once the load balancer has stopped sending new
+    requests, is the server stopped. Requests still in flight then get up to `shutdown_timeout`
+    seconds to finish.
+*/
+
+use crate::problem::{ApiError, Problem};
+use crate::repository::PersonRepository;
+use actix_web::dev::ServerHandle;
+use actix_web::{get, web};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+// Shared between the /readyz handler and the signal handler
+#[derive(Debug)]
+pub struct Readiness(AtomicBool);
+
+impl Readiness {
+    pub fn new() -> Self {
+        Readiness(AtomicBool::new(true))
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set_ready(&self, ready: bool) {
+        self.0.store(ready, Ordering::SeqCst);
+    }
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Status {
+    pub status: String,
+}
+
+fn status(status: &str) -> web::Json<Status> {
+    web::Json(Status {
+        status: status.to_string(),
+    })
+}
+
+#[utoipa::path(
+    tag = "health",
+    responses((status = 200, description = "The server is up", body = Status))
+)]
+#[get("/healthz")]
+pub async fn healthz() -> web::Json<Status> {
+    status("ok")
+}
+
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready for traffic", body = Status),
+        (status = 503, description = "Shutting down, or the database can't be reached", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+#[get("/readyz")]
+pub async fn readyz(
+    readiness: web::Data<Readiness>,
+    repo: web::Data<dyn PersonRepository>,
+) -> Result<web::Json<Status>, ApiError> {
+    if !readiness.is_ready() {
+        return Err(ApiError::Unavailable("Shutting down".to_string()));
+    }
+
+    web::block(move || repo.ping())
+        .await?
+        .map_err(|e| ApiError::Unavailable(format!("Database unavailable: {}", e)))?;
+    Ok(status("ready"))
+}
+
+#[cfg(unix)]
+async fn terminate() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn terminate() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+// Replaces actix's own signal handling, which stops the server immediately,
+// so start the server with `.disable_signals()`
+pub async fn shutdown_on_signal(
+    handle: ServerHandle,
+    readiness: web::Data<Readiness>,
+    delay: Duration,
+) {
+    terminate().await;
+
+    tracing::info!(
+        delay_secs = delay.as_secs(),
+        "shutting down, no longer ready"
+    );
+    readiness.set_ready(false);
+    tokio::time::sleep(delay).await;
+
+    // Graceful: stop accepting connections, wait for running requests up to shutdown_timeout
+    handle.stop(true).await;
+}
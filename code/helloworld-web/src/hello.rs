@@ -0,0 +1,46 @@
+// The original hello world handlers, "/" now serves the chat client
+
+use crate::auth::Claims;
+use crate::problem::Problem;
+use actix_web::middleware::from_fn;
+use actix_web::{get, post, web, HttpResponse, Responder};
+
+// The chat client from static/, built into the binary
+const INDEX_HTML: &str = include_str!("../static/index.html");
+
+#[utoipa::path(
+    tag = "hello",
+    responses((status = 200, description = "The chat client", body = String, content_type = "text/html"))
+)]
+#[get("/")]
+pub async fn hello() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(INDEX_HTML)
+}
+
+#[utoipa::path(
+    tag = "hello",
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = 200, description = "The request body", body = String, content_type = "text/plain"),
+        (status = 401, description = "Missing or invalid token", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer" = []))
+)]
+#[post("/echo", wrap = "from_fn(crate::auth::require_auth)")]
+pub async fn echo(claims: web::ReqData<Claims>, req_body: String) -> impl Responder {
+    tracing::info!(user = %claims.sub, body_bytes = req_body.len(), "echo");
+    HttpResponse::Ok().body(req_body)
+}
+
+// Registered with .route() instead of a macro, so the path and method are spelled out here
+#[utoipa::path(
+    get,
+    path = "/hey",
+    tag = "hello",
+    responses((status = 200, description = "A greeting", body = String, content_type = "text/plain"))
+)]
+pub async fn manual_hello() -> impl Responder {
+    HttpResponse::Ok().body("Hey there!")
+}
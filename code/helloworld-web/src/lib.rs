@@ -0,0 +1,124 @@
+// The library crate holds the API, main.rs starts the server
+#![crate_name = "helloworld_web"]
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::middleware::{from_fn, Logger};
+use actix_web::{web, App};
+use auth::JwtAuth;
+use chat::ChatHub;
+use health::Readiness;
+use repository::PersonRepository;
+use settings::Settings;
+use std::sync::Arc;
+
+pub mod auth;
+pub mod chat;
+pub mod files;
+pub mod health;
+pub mod hello;
+pub mod openapi;
+pub mod people;
+pub mod person;
+pub mod problem;
+pub mod repository;
+pub mod settings;
+pub mod telemetry;
+pub mod wordcount;
+
+// Everything the handlers share. main.rs builds it once and every worker's App gets a clone,
+// which only copies the settings, the rest are Arcs pointing at the same values.
+#[derive(Clone)]
+pub struct AppState {
+    pub repository: web::Data<dyn PersonRepository>,
+    pub auth: web::Data<JwtAuth>,
+    // Flipped to not ready on shutdown, so keep a clone to do that
+    pub readiness: web::Data<Readiness>,
+    pub chat: web::Data<ChatHub>,
+    pub settings: Settings,
+}
+
+impl AppState {
+    // Starts out ready, with an empty chat hub. To share either of them, set the field:
+    //
+    //     AppState { readiness: readiness.clone(), ..AppState::new(repository, auth, settings) }
+    pub fn new(repository: Arc<dyn PersonRepository>, auth: JwtAuth, settings: Settings) -> Self {
+        AppState {
+            repository: web::Data::from(repository),
+            auth: web::Data::new(auth),
+            readiness: web::Data::new(Readiness::new()),
+            chat: web::Data::new(ChatHub::new()),
+            settings,
+        }
+    }
+}
+
+// The whole application, routes and middleware, shared by main.rs and the tests
+pub fn app(
+    state: AppState,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    // The last middleware wrapped is the outermost one, so the Logger sees
+    // the X-Request-Id header added by trace_request. %D is the latency in milliseconds.
+    App::new()
+        .wrap(from_fn(telemetry::trace_request))
+        .wrap(Logger::new(
+            "%a \"%r\" %s %b %{x-request-id}o latency_ms=%D",
+        ))
+        .configure(configure(state))
+}
+
+// The route table, for embedding the API in another App:
+//
+//     App::new().configure(helloworld_web::configure(state))
+pub fn configure(state: AppState) -> impl FnOnce(&mut web::ServiceConfig) {
+    let AppState {
+        repository,
+        auth,
+        readiness,
+        chat,
+        settings,
+    } = state;
+    let json_limit = settings.json_limit;
+    let payload_limit = settings.payload_limit;
+    let wordcount_limit = wordcount::WordCountLimit(settings.wordcount_limit);
+    let uploads = files::Uploads {
+        dir: settings.upload_dir.clone(),
+        limit: settings.upload_limit,
+    };
+
+    move |cfg| {
+        cfg.app_data(repository)
+            .app_data(auth)
+            .app_data(readiness)
+            .app_data(chat)
+            .app_data(web::Data::new(uploads))
+            .app_data(web::Data::new(wordcount_limit))
+            .app_data(
+                web::JsonConfig::default()
+                    .limit(json_limit)
+                    .error_handler(problem::json_error_handler),
+            )
+            .app_data(web::PayloadConfig::new(payload_limit))
+            .app_data(web::PathConfig::default().error_handler(problem::path_error_handler))
+            .service(health::healthz)
+            .service(health::readyz)
+            .service(auth::login)
+            .service(hello::hello)
+            .service(hello::echo)
+            .route("/hey", web::get().to(hello::manual_hello))
+            .configure(people::routes)
+            .service(files::upload)
+            .service(files::download)
+            .service(wordcount::wordcount)
+            .service(chat::ws)
+            .service(openapi::swagger_ui());
+    }
+}
@@ -1,28 +1,52 @@
-use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
+use actix_web::http::KeepAlive;
+use actix_web::HttpServer;
+use clap::Parser;
+use helloworld_web::auth::JwtAuth;
+use helloworld_web::health;
+use helloworld_web::repository::{PersonRepository, SqliteRepository};
+use helloworld_web::settings::{Cli, Settings};
+use helloworld_web::telemetry;
+use helloworld_web::AppState;
+use std::sync::Arc;
+use std::time::Duration;
 
-#[get("/")]
-async fn hello() -> impl Responder {
-    HttpResponse::Ok().body("Hello world!")
-}
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::load(&cli).map_err(std::io::Error::other)?;
+    telemetry::init(&settings.log_level);
+    tracing::info!(?settings, "starting");
 
-#[post("/echo")]
-async fn echo(req_body: String) -> impl Responder {
-    HttpResponse::Ok().body(req_body)
-}
+    let repository =
+        SqliteRepository::open(&settings.database_path).map_err(std::io::Error::other)?;
 
-async fn manual_hello() -> impl Responder {
-    HttpResponse::Ok().body("Hey there!")
-}
+    // Created once, outside the closure, so every worker shares the same repository,
+    // JWT keys, readiness flag and chat hub. The hub being shared means everyone in a room
+    // sees each other's messages, whichever worker their connection landed on.
+    // Going through Arc<dyn ..> lets handlers work with any PersonRepository.
+    let repository: Arc<dyn PersonRepository> = Arc::new(repository);
+    let auth = JwtAuth::from_settings(&settings)?;
+    let state = AppState::new(repository, auth, settings.clone());
+    let readiness = state.readiness.clone();
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| {
-        App::new()
-            .service(hello)
-            .service(echo)
-            .route("/hey", web::get().to(manual_hello))
-    })
-    .bind(("127.0.0.1", 8080))?
-    .run()
-    .await
+    let keep_alive = match settings.keep_alive() {
+        Some(timeout) => KeepAlive::Timeout(timeout),
+        None => KeepAlive::Disabled,
+    };
+
+    let server = HttpServer::new(move || helloworld_web::app(state.clone()))
+        .workers(settings.workers)
+        .keep_alive(keep_alive)
+        .shutdown_timeout(settings.shutdown_timeout)
+        // Signals are handled by health::shutdown_on_signal instead
+        .disable_signals()
+        .bind((settings.host.as_str(), settings.port))?
+        .run();
+
+    actix_web::rt::spawn(health::shutdown_on_signal(
+        server.handle(),
+        readiness,
+        Duration::from_secs(settings.shutdown_delay),
+    ));
+    server.await
 }
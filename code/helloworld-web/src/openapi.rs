@@ -0,0 +1,62 @@
+/*
+    The OpenAPI description of the routes, generated by utoipa from the
+    `#[utoipa::path]` attributes on the handlers.
+
+    GET /openapi.json   the spec
+    GET /docs/          Swagger UI for trying out the API
+
+    A new handler has to be added to `paths(...)` below, tests/openapi.rs fails otherwise.
+*/
+
+use crate::{auth, chat, files, health, hello, people, wordcount};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "helloworld-web", description = "The hello world and people API"),
+    paths(
+        health::healthz,
+        health::readyz,
+        auth::login,
+        hello::hello,
+        hello::echo,
+        hello::manual_hello,
+        people::list_people,
+        people::get_person,
+        people::create_person,
+        people::replace_person,
+        people::update_person,
+        people::delete_person,
+        files::upload,
+        files::download,
+        wordcount::wordcount,
+        chat::ws,
+    ),
+    modifiers(&BearerAuth)
+)]
+pub struct ApiDoc;
+
+// The "bearer" security scheme used by the protected routes, a token from /login
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+// Serves both the spec and the UI
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi())
+}
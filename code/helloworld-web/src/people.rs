@@ -0,0 +1,168 @@
+// CRUD handlers for the /people resource.
+// Anyone can read, changes need a token for a user with the editor role.
+
+use crate::person::{NewPerson, Person, PersonPatch};
+use crate::problem::{ApiError, Problem};
+use crate::repository::{PersonRepository, RepositoryError};
+use actix_web::middleware::from_fn;
+use actix_web::{delete, get, patch, post, put, web, HttpResponse};
+
+// Shared by all the workers, web::Data is an Arc around the repository
+type Repository = web::Data<dyn PersonRepository>;
+
+pub fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_people)
+        .service(get_person)
+        .service(create_person)
+        .service(replace_person)
+        .service(update_person)
+        .service(delete_person);
+}
+
+fn not_found(id: u32) -> ApiError {
+    ApiError::NotFound(format!("No person with id {}", id))
+}
+
+// Database calls block, so they run on actix's thread pool for blocking work
+// instead of stalling the worker that is running our async handlers
+async fn run<T, F>(repo: &Repository, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn PersonRepository) -> Result<T, RepositoryError> + Send + 'static,
+{
+    let repo = repo.clone();
+    let result = web::block(move || f(repo.get_ref())).await?;
+    Ok(result?)
+}
+
+#[utoipa::path(
+    tag = "people",
+    responses(
+        (status = 200, description = "Everyone", body = Vec<Person>),
+    )
+)]
+#[get("/people")]
+async fn list_people(repo: Repository) -> Result<web::Json<Vec<Person>>, ApiError> {
+    let people = run(&repo, |repo| repo.list()).await?;
+    Ok(web::Json(people))
+}
+
+#[utoipa::path(
+    tag = "people",
+    params(("id" = u32, Path, description = "Id of the person")),
+    responses(
+        (status = 200, description = "The person", body = Person),
+        (status = 404, description = "No person with that id", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+#[get("/people/{id}")]
+async fn get_person(repo: Repository, id: web::Path<u32>) -> Result<web::Json<Person>, ApiError> {
+    let id = id.into_inner();
+    let person = run(&repo, move |repo| repo.get(id))
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    Ok(web::Json(person))
+}
+
+#[utoipa::path(
+    tag = "people",
+    request_body = NewPerson,
+    responses(
+        (status = 201, description = "The new person, its URL is in the Location header", body = Person),
+        (status = 422, description = "Invalid fields", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "The user is not an editor", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer" = []))
+)]
+#[post("/people", wrap = "from_fn(crate::auth::require_editor)")]
+async fn create_person(
+    repo: Repository,
+    body: web::Json<NewPerson>,
+) -> Result<HttpResponse, ApiError> {
+    let new_person = body.into_inner();
+    new_person.validate().map_err(ApiError::Validation)?;
+
+    let person = run(&repo, move |repo| repo.insert(new_person)).await?;
+    Ok(HttpResponse::Created()
+        .insert_header(("Location", format!("/people/{}", person.id)))
+        .json(person))
+}
+
+#[utoipa::path(
+    tag = "people",
+    params(("id" = u32, Path, description = "Id of the person")),
+    request_body = NewPerson,
+    responses(
+        (status = 200, description = "The replaced person", body = Person),
+        (status = 404, description = "No person with that id", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "The user is not an editor", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer" = []))
+)]
+#[put("/people/{id}", wrap = "from_fn(crate::auth::require_editor)")]
+async fn replace_person(
+    repo: Repository,
+    id: web::Path<u32>,
+    body: web::Json<NewPerson>,
+) -> Result<web::Json<Person>, ApiError> {
+    let id = id.into_inner();
+    let new_person = body.into_inner();
+    new_person.validate().map_err(ApiError::Validation)?;
+
+    let person = run(&repo, move |repo| repo.replace(id, new_person))
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    Ok(web::Json(person))
+}
+
+#[utoipa::path(
+    tag = "people",
+    params(("id" = u32, Path, description = "Id of the person")),
+    request_body = PersonPatch,
+    responses(
+        (status = 200, description = "The updated person", body = Person),
+        (status = 404, description = "No person with that id", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid fields", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "The user is not an editor", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer" = []))
+)]
+#[patch("/people/{id}", wrap = "from_fn(crate::auth::require_editor)")]
+async fn update_person(
+    repo: Repository,
+    id: web::Path<u32>,
+    body: web::Json<PersonPatch>,
+) -> Result<web::Json<Person>, ApiError> {
+    let id = id.into_inner();
+    let patch = body.into_inner();
+    patch.validate().map_err(ApiError::Validation)?;
+
+    let person = run(&repo, move |repo| repo.update(id, patch))
+        .await?
+        .ok_or_else(|| not_found(id))?;
+    Ok(web::Json(person))
+}
+
+#[utoipa::path(
+    tag = "people",
+    params(("id" = u32, Path, description = "Id of the person")),
+    responses(
+        (status = 204, description = "The person was deleted"),
+        (status = 404, description = "No person with that id", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "The user is not an editor", body = Problem, content_type = "application/problem+json"),
+    ),
+    security(("bearer" = []))
+)]
+#[delete("/people/{id}", wrap = "from_fn(crate::auth::require_editor)")]
+async fn delete_person(repo: Repository, id: web::Path<u32>) -> Result<HttpResponse, ApiError> {
+    let id = id.into_inner();
+    if !run(&repo, move |repo| repo.remove(id)).await? {
+        return Err(not_found(id));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
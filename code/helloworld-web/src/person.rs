@@ -0,0 +1,99 @@
+// The Person from `structs_and_traits`, now sent and received as JSON
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Person {
+    pub id: u32,
+    pub name: String,
+    pub email: String,
+}
+
+// Body of POST and PUT, the id always comes from the server or the URL
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct NewPerson {
+    pub name: String,
+    pub email: String,
+}
+
+// Body of PATCH, only the fields that are present get updated
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct PersonPatch {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: &'static str,
+}
+
+impl NewPerson {
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        validate_name(&self.name, &mut errors);
+        validate_email(&self.email, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl PersonPatch {
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+        if let Some(name) = &self.name {
+            validate_name(name, &mut errors);
+        }
+        if let Some(email) = &self.email {
+            validate_email(email, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn validate_name(name: &str, errors: &mut Vec<FieldError>) {
+    if name.trim().is_empty() {
+        errors.push(FieldError {
+            field: "name",
+            message: "must not be empty",
+        });
+    } else if name.chars().count() > 100 {
+        errors.push(FieldError {
+            field: "name",
+            message: "must be at most 100 characters",
+        });
+    }
+}
+
+// Not a full RFC 5322 check, just enough to catch obvious typos
+fn validate_email(email: &str, errors: &mut Vec<FieldError>) {
+    let valid = match email.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+
+    if !valid {
+        errors.push(FieldError {
+            field: "email",
+            message: "must be a valid email address",
+        });
+    }
+}
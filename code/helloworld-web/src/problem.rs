@@ -0,0 +1,170 @@
+/*
+    Errors are returned as "problem details" (RFC 7807):
+
+    HTTP/1.1 422 Unprocessable Entity
+    Content-Type: application/problem+json
+
+    {"type":"about:blank","title":"Validation failed","status":422,
+     "errors":[{"field":"email","message":"must be a valid email address"}]}
+*/
+
+use crate::person::FieldError;
+use crate::repository::RepositoryError;
+use actix_multipart::MultipartError;
+use actix_web::error::{BlockingError, JsonPayloadError, PathError, PayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    PayloadTooLarge(String),
+    Unavailable(String),
+    UnsupportedMediaType(String),
+    Validation(Vec<FieldError>),
+    Internal(String),
+}
+
+impl ApiError {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "Not found",
+            Self::BadRequest(_) => "Bad request",
+            Self::Unauthorized(_) => "Unauthorized",
+            Self::Forbidden(_) => "Forbidden",
+            Self::PayloadTooLarge(_) => "Payload too large",
+            Self::Unavailable(_) => "Service unavailable",
+            Self::UnsupportedMediaType(_) => "Unsupported media type",
+            Self::Validation(_) => "Validation failed",
+            Self::Internal(_) => "Internal server error",
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::NotFound(detail)
+            | Self::BadRequest(detail)
+            | Self::Unauthorized(detail)
+            | Self::Forbidden(detail)
+            | Self::PayloadTooLarge(detail)
+            | Self::Unavailable(detail)
+            | Self::UnsupportedMediaType(detail) => write!(f, "{}: {}", self.title(), detail),
+            Self::Validation(errors) => write!(f, "{}: {:?}", self.title(), errors),
+            Self::Internal(detail) => write!(f, "{}: {}", self.title(), detail),
+        }
+    }
+}
+
+// Lets handlers return Result<_, ApiError> and use `?`
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let (detail, errors) = match self {
+            Self::NotFound(detail)
+            | Self::BadRequest(detail)
+            | Self::Unauthorized(detail)
+            | Self::Forbidden(detail)
+            | Self::PayloadTooLarge(detail)
+            | Self::Unavailable(detail)
+            | Self::UnsupportedMediaType(detail) => (Some(detail.clone()), Vec::new()),
+            Self::Validation(errors) => (None, errors.clone()),
+            // The details of internal errors are for our logs, not for clients
+            Self::Internal(detail) => {
+                tracing::error!(%detail, "internal error");
+                (None, Vec::new())
+            }
+        };
+
+        let problem = Problem {
+            problem_type: "about:blank",
+            title: self.title(),
+            status: self.status_code().as_u16(),
+            detail,
+            errors,
+        };
+
+        let mut response = HttpResponse::build(self.status_code());
+        // 401 responses must say which authentication scheme we expect
+        if let Self::Unauthorized(_) = self {
+            response.insert_header(("WWW-Authenticate", "Bearer"));
+        }
+        response
+            .content_type("application/problem+json")
+            .json(problem)
+    }
+}
+
+impl From<RepositoryError> for ApiError {
+    fn from(e: RepositoryError) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+impl From<BlockingError> for ApiError {
+    fn from(e: BlockingError) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+// Malformed multipart forms, or ones cut off in the middle
+impl From<MultipartError> for ApiError {
+    fn from(e: MultipartError) -> Self {
+        Self::BadRequest(e.to_string())
+    }
+}
+
+// A body stream that broke off, like a client hanging up in the middle of an upload
+impl From<PayloadError> for ApiError {
+    fn from(e: PayloadError) -> Self {
+        Self::BadRequest(e.to_string())
+    }
+}
+
+// Used with `web::JsonConfig::error_handler`, so malformed bodies get problem details too
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        // Bodies over `Settings::json_limit`
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            ApiError::PayloadTooLarge(err.to_string()).into()
+        }
+        _ => ApiError::BadRequest(err.to_string()).into(),
+    }
+}
+
+// Same for ids that are not numbers, like /people/abc
+pub fn path_error_handler(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(err.to_string()).into()
+}
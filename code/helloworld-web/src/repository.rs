@@ -0,0 +1,185 @@
+/*
+    Storage for people, behind a trait so handlers don't care where the data lives.
+
+    SqliteRepository keeps everything in a local SQLite file, or in memory for tests.
+    The methods are blocking, so handlers call them through `web::block`.
+*/
+
+use crate::person::{NewPerson, Person, PersonPatch};
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::Path;
+use std::time::Duration;
+
+// Every schema change gets a new file here, they are compiled into the binary.
+// The index + 1 is the schema version, stored in SQLite's `user_version`.
+const MIGRATIONS: [&str; 1] = [include_str!("../migrations/0001_create_people.sql")];
+
+pub trait PersonRepository: Send + Sync {
+    fn list(&self) -> Result<Vec<Person>, RepositoryError>;
+    fn get(&self, id: u32) -> Result<Option<Person>, RepositoryError>;
+    fn insert(&self, new_person: NewPerson) -> Result<Person, RepositoryError>;
+    fn replace(&self, id: u32, new_person: NewPerson) -> Result<Option<Person>, RepositoryError>;
+    fn update(&self, id: u32, patch: PersonPatch) -> Result<Option<Person>, RepositoryError>;
+    fn remove(&self, id: u32) -> Result<bool, RepositoryError>;
+    // Checks that the storage can be reached, for the readiness probe
+    fn ping(&self) -> Result<(), RepositoryError>;
+}
+
+#[derive(Debug)]
+pub enum RepositoryError {
+    Pool(r2d2::Error),
+    Sqlite(rusqlite::Error),
+}
+
+impl Display for RepositoryError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Pool(e) => write!(f, "connection pool error: {}", e),
+            Self::Sqlite(e) => write!(f, "sqlite error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {}
+
+impl From<r2d2::Error> for RepositoryError {
+    fn from(e: r2d2::Error) -> Self {
+        Self::Pool(e)
+    }
+}
+
+impl From<rusqlite::Error> for RepositoryError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Sqlite(e)
+    }
+}
+
+pub struct SqliteRepository {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+impl SqliteRepository {
+    // Opens (or creates) the database file and brings the schema up to date
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RepositoryError> {
+        let manager = SqliteConnectionManager::file(path).with_init(|conn| {
+            // WAL lets readers carry on while another connection writes,
+            // busy_timeout makes writers wait for each other instead of failing
+            conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA busy_timeout = 5000;")
+        });
+        let pool = Pool::builder().max_size(8).build(manager)?;
+        Self::with_pool(pool)
+    }
+
+    // Every in-memory connection is its own empty database,
+    // so the pool holds a single connection that is never closed
+    pub fn in_memory() -> Result<Self, RepositoryError> {
+        let pool = Pool::builder()
+            .max_size(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .build(SqliteConnectionManager::memory())?;
+        Self::with_pool(pool)
+    }
+
+    fn with_pool(pool: Pool<SqliteConnectionManager>) -> Result<Self, RepositoryError> {
+        let mut conn = pool.get()?;
+        migrate(&mut conn)?;
+        Ok(SqliteRepository { pool })
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), RepositoryError> {
+    let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        // Each migration and its version bump either both happen or neither does
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", i as u32 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+fn person_from_row(row: &Row) -> rusqlite::Result<Person> {
+    Ok(Person {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        email: row.get(2)?,
+    })
+}
+
+impl PersonRepository for SqliteRepository {
+    fn list(&self) -> Result<Vec<Person>, RepositoryError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare("SELECT id, name, email FROM people ORDER BY id")?;
+        let people = stmt
+            .query_map([], person_from_row)?
+            .collect::<rusqlite::Result<Vec<Person>>>()?;
+        Ok(people)
+    }
+
+    fn get(&self, id: u32) -> Result<Option<Person>, RepositoryError> {
+        let conn = self.pool.get()?;
+        let person = conn
+            .query_row(
+                "SELECT id, name, email FROM people WHERE id = ?1",
+                [id],
+                person_from_row,
+            )
+            .optional()?;
+        Ok(person)
+    }
+
+    fn insert(&self, new_person: NewPerson) -> Result<Person, RepositoryError> {
+        let conn = self.pool.get()?;
+        let person = conn.query_row(
+            "INSERT INTO people (name, email) VALUES (?1, ?2) RETURNING id, name, email",
+            params![new_person.name, new_person.email],
+            person_from_row,
+        )?;
+        Ok(person)
+    }
+
+    fn replace(&self, id: u32, new_person: NewPerson) -> Result<Option<Person>, RepositoryError> {
+        let conn = self.pool.get()?;
+        let person = conn
+            .query_row(
+                "UPDATE people SET name = ?2, email = ?3 WHERE id = ?1 RETURNING id, name, email",
+                params![id, new_person.name, new_person.email],
+                person_from_row,
+            )
+            .optional()?;
+        Ok(person)
+    }
+
+    fn update(&self, id: u32, patch: PersonPatch) -> Result<Option<Person>, RepositoryError> {
+        let conn = self.pool.get()?;
+        // COALESCE keeps the current value for the fields missing from the patch
+        let person = conn
+            .query_row(
+                "UPDATE people SET name = COALESCE(?2, name), email = COALESCE(?3, email)
+                 WHERE id = ?1 RETURNING id, name, email",
+                params![id, patch.name, patch.email],
+                person_from_row,
+            )
+            .optional()?;
+        Ok(person)
+    }
+
+    fn remove(&self, id: u32) -> Result<bool, RepositoryError> {
+        let conn = self.pool.get()?;
+        let deleted = conn.execute("DELETE FROM people WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    }
+
+    fn ping(&self) -> Result<(), RepositoryError> {
+        // A short timeout, a probe waiting the pool's default 30s would itself time out
+        let conn = self.pool.get_timeout(Duration::from_secs(1))?;
+        conn.query_row("SELECT 1", [], |_| Ok(()))?;
+        Ok(())
+    }
+}
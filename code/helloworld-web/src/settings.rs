@@ -0,0 +1,215 @@
+/*
+    Settings are layered, each source overriding the ones before it:
+
+    1. the defaults in `Settings::default()`
+    2. a TOML file, `helloworld-web.toml` or the one passed with `--config`
+    3. APP_* environment variables, like `APP_PORT=9000`
+    4. command line flags, like `--port 9000`
+*/
+
+use clap::Parser;
+use config::{Config, ConfigError, Environment, File};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DEFAULT_CONFIG_FILE: &str = "helloworld-web.toml";
+
+// Debug is written out below, so the settings can be logged without the JWT secret
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    pub host: String,
+    pub port: u16,
+    pub workers: usize,
+    pub log_level: String,
+    // Largest JSON body accepted, in bytes
+    pub json_limit: usize,
+    // Largest raw body accepted by handlers like `echo`, in bytes
+    pub payload_limit: usize,
+    // Seconds an idle connection is kept open, 0 closes it after every request
+    pub keep_alive: u64,
+    pub database_path: String,
+    // Directory for files sent to /upload, and the largest upload accepted in bytes
+    pub upload_dir: PathBuf,
+    pub upload_limit: u64,
+    // Largest file accepted by /wordcount, in bytes. The whole text is kept in memory.
+    pub wordcount_limit: usize,
+    // HS256 signing secret. Without one a random secret is generated,
+    // and tokens stop working when the server restarts.
+    pub jwt_secret: Option<String>,
+    // PEM files. With a public key, RS256 tokens signed by its private key are accepted,
+    // with both, /login issues RS256 tokens instead of HS256 ones.
+    pub jwt_rsa_private_key: Option<PathBuf>,
+    pub jwt_rsa_public_key: Option<PathBuf>,
+    // Lifetime of the tokens issued by /login, in seconds
+    pub jwt_ttl: u64,
+    // Seconds between /readyz failing and the server stopping, on SIGTERM
+    pub shutdown_delay: u64,
+    // Seconds running requests get to finish once the server stops
+    pub shutdown_timeout: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            host: String::from("127.0.0.1"),
+            port: 8080,
+            workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            log_level: String::from("info"),
+            json_limit: 32 * 1024,
+            payload_limit: 256 * 1024,
+            keep_alive: 5,
+            // Created in the working directory, next to where `cargo run` is started
+            database_path: String::from("people.db"),
+            upload_dir: PathBuf::from("uploads"),
+            upload_limit: 100 * 1024 * 1024,
+            wordcount_limit: 8 * 1024 * 1024,
+            jwt_secret: None,
+            jwt_rsa_private_key: None,
+            jwt_rsa_public_key: None,
+            jwt_ttl: 60 * 60,
+            shutdown_delay: 5,
+            shutdown_timeout: 30,
+        }
+    }
+}
+
+impl fmt::Debug for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Settings")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("workers", &self.workers)
+            .field("log_level", &self.log_level)
+            .field("json_limit", &self.json_limit)
+            .field("payload_limit", &self.payload_limit)
+            .field("keep_alive", &self.keep_alive)
+            .field("database_path", &self.database_path)
+            .field("upload_dir", &self.upload_dir)
+            .field("upload_limit", &self.upload_limit)
+            .field("wordcount_limit", &self.wordcount_limit)
+            // Whether there is a secret, never the secret itself
+            .field("jwt_secret", &self.jwt_secret.as_ref().map(|_| "***"))
+            .field("jwt_rsa_private_key", &self.jwt_rsa_private_key)
+            .field("jwt_rsa_public_key", &self.jwt_rsa_public_key)
+            .field("jwt_ttl", &self.jwt_ttl)
+            .field("shutdown_delay", &self.shutdown_delay)
+            .field("shutdown_timeout", &self.shutdown_timeout)
+            .finish()
+    }
+}
+
+// Every flag is optional, a missing flag leaves the value from the other sources alone.
+// There are deliberately no flags for the JWT keys, command lines are visible to other users.
+#[derive(Debug, Default, Parser)]
+#[command(version, about = "Hello world, and a people API, on actix-web")]
+pub struct Cli {
+    /// TOML file to read settings from [default: helloworld-web.toml, if it exists]
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Address to listen on
+    #[arg(long)]
+    pub host: Option<String>,
+
+    /// Port to listen on
+    #[arg(short, long)]
+    pub port: Option<u16>,
+
+    /// Number of worker threads
+    #[arg(short, long)]
+    pub workers: Option<usize>,
+
+    /// Log level: error, warn, info, debug or trace
+    #[arg(long)]
+    pub log_level: Option<String>,
+
+    /// Largest JSON body accepted, in bytes
+    #[arg(long)]
+    pub json_limit: Option<usize>,
+
+    /// Largest raw request body accepted, in bytes
+    #[arg(long)]
+    pub payload_limit: Option<usize>,
+
+    /// Seconds to keep idle connections open, 0 to disable keep-alive
+    #[arg(long)]
+    pub keep_alive: Option<u64>,
+
+    /// SQLite database file
+    #[arg(long)]
+    pub database_path: Option<String>,
+
+    /// Directory to store uploaded files in
+    #[arg(long)]
+    pub upload_dir: Option<PathBuf>,
+
+    /// Largest upload accepted, in bytes
+    #[arg(long)]
+    pub upload_limit: Option<u64>,
+
+    /// Largest file accepted by /wordcount, in bytes
+    #[arg(long)]
+    pub wordcount_limit: Option<usize>,
+
+    /// Seconds to keep serving, while /readyz fails, after SIGTERM
+    #[arg(long)]
+    pub shutdown_delay: Option<u64>,
+
+    /// Seconds running requests get to finish when the server stops
+    #[arg(long)]
+    pub shutdown_timeout: Option<u64>,
+}
+
+impl Settings {
+    pub fn load(cli: &Cli) -> Result<Self, ConfigError> {
+        // An explicit --config file must exist, the default one is optional
+        let file = match &cli.config {
+            Some(path) => File::from(path.as_path()).required(true),
+            None => File::with_name(DEFAULT_CONFIG_FILE).required(false),
+        };
+
+        Config::builder()
+            .add_source(Config::try_from(&Settings::default())?)
+            .add_source(file)
+            .add_source(Environment::with_prefix("APP").try_parsing(true))
+            .set_override_option("host", cli.host.clone())?
+            .set_override_option("port", cli.port)?
+            .set_override_option("workers", cli.workers.map(|n| n as u64))?
+            .set_override_option("log_level", cli.log_level.clone())?
+            .set_override_option("json_limit", cli.json_limit.map(|n| n as u64))?
+            .set_override_option("payload_limit", cli.payload_limit.map(|n| n as u64))?
+            .set_override_option("keep_alive", cli.keep_alive)?
+            .set_override_option("database_path", cli.database_path.clone())?
+            .set_override_option(
+                "upload_dir",
+                cli.upload_dir.as_ref().map(|dir| dir.display().to_string()),
+            )?
+            .set_override_option("upload_limit", cli.upload_limit)?
+            .set_override_option("wordcount_limit", cli.wordcount_limit.map(|n| n as u64))?
+            .set_override_option("shutdown_delay", cli.shutdown_delay)?
+            .set_override_option("shutdown_timeout", cli.shutdown_timeout)?
+            .build()?
+            .try_deserialize::<Settings>()?
+            .validate()
+    }
+
+    // Values that deserialize fine but can't work. Actix panics on 0 workers,
+    // better to say which setting is wrong before anything starts.
+    fn validate(self) -> Result<Self, ConfigError> {
+        if self.workers == 0 {
+            return Err(ConfigError::Message(String::from(
+                "workers must be at least 1",
+            )));
+        }
+        Ok(self)
+    }
+
+    pub fn keep_alive(&self) -> Option<Duration> {
+        match self.keep_alive {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        }
+    }
+}
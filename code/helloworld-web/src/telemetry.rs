@@ -0,0 +1,103 @@
+/*
+    Logging with the `tracing` crate.
+
+    Logs are written to stdout as one JSON object per line. Everything logged while
+    handling a request carries the request's span, so it can be found by request_id:
+
+    {"level":"INFO","fields":{"message":"echo","body_bytes":5},
+     "span":{"request_id":"5c1a..","method":"POST","path":"/echo","name":"request"}, ..}
+
+    When the response is ready the span gets the status and latency_ms, and
+    a "finished" event logs them, so slow requests can be queried by number.
+*/
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::HttpMessage;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Sets up the JSON subscriber. RUST_LOG, when set, wins over `log_level`
+// so a single module can be turned up without touching the config.
+// Records from the `log` crate, like actix's Logger, are forwarded to tracing.
+pub fn init(log_level: &str) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level));
+
+    tracing_subscriber::fmt()
+        .json()
+        .with_env_filter(filter)
+        .with_current_span(true)
+        .with_span_list(false)
+        .init();
+}
+
+// Available to handlers as `web::ReqData<RequestId>`
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Reuses the caller's X-Request-Id, so one id can follow a request across services.
+// Anything that is not a short, printable value is replaced, it ends up in our logs.
+fn request_id(req: &ServiceRequest) -> RequestId {
+    let incoming = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic()));
+
+    match incoming {
+        Some(id) => RequestId(id.to_string()),
+        None => RequestId(Uuid::new_v4().to_string()),
+    }
+}
+
+// Middleware, used with `middleware::from_fn(telemetry::trace_request)`
+pub async fn trace_request(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let id = request_id(&req);
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.path(),
+        status = Empty,
+        latency_ms = Empty,
+    );
+    req.extensions_mut().insert(id.clone());
+
+    let started = Instant::now();
+    let result = next.call(req).instrument(span.clone()).await;
+    span.record("latency_ms", started.elapsed().as_secs_f64() * 1000.0);
+
+    let mut res = match result {
+        Ok(res) => res,
+        Err(e) => {
+            span.in_scope(|| tracing::warn!(error = %e, "failed"));
+            return Err(e);
+        }
+    };
+    span.record("status", res.status().as_u16());
+    span.in_scope(|| tracing::info!("finished"));
+
+    // Hand the id back, so clients can quote it when reporting a problem
+    if let Ok(value) = HeaderValue::from_str(&id.0) {
+        res.headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    Ok(res)
+}
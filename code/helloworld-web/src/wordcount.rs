@@ -0,0 +1,155 @@
+/*
+    Word counts from `word_count_problem`, over HTTP.
+
+    The text comes either as JSON:
+
+        curl -H "Content-Type: application/json" \
+             -d '{"text":"Travel, travel!","words":["travel"]}' localhost:8080/wordcount
+
+    or as a file in a multipart form, with the words in one or more `words` fields:
+
+        curl -F file=@gullivers_travels.txt -F words=Gutenberg,travel localhost:8080/wordcount
+
+    Counting a whole book takes a while, so it runs on the blocking thread pool
+    and the async workers keep serving other requests meanwhile.
+*/
+
+use crate::person::FieldError;
+use crate::problem::{ApiError, Problem};
+use actix_multipart::Multipart;
+use actix_web::{post, web, FromRequest, HttpMessage, HttpRequest};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use word_count_problem::find_word_occurances;
+
+const MAX_WORDS: usize = 100;
+
+// The largest file accepted in a multipart form, from the settings.
+// JSON bodies are limited by `json_limit` like everywhere else.
+#[derive(Debug, Clone, Copy)]
+pub struct WordCountLimit(pub usize);
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WordCountRequest {
+    pub text: String,
+    pub words: Vec<String>,
+}
+
+// Only describes the multipart form for the docs, the form is read field by field
+#[derive(ToSchema)]
+pub struct WordCountForm {
+    #[schema(content_media_type = "text/plain")]
+    pub file: Vec<u8>,
+    // Comma separated, the field can also be repeated
+    pub words: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WordCount {
+    // Keyed by the lowercased search word
+    pub counts: BTreeMap<String, usize>,
+}
+
+fn words_error(message: &'static str) -> ApiError {
+    ApiError::Validation(vec![FieldError {
+        field: "words",
+        message,
+    }])
+}
+
+// Reads a multipart form into the text of its `file` (or `text`) field and the search words
+async fn read_form(mut form: Multipart, limit: usize) -> Result<(Vec<u8>, Vec<String>), ApiError> {
+    let mut text = Vec::new();
+    let mut words = Vec::new();
+
+    while let Some(field) = form.next().await {
+        let mut field = field?;
+        let name = field.name().unwrap_or_default().to_string();
+
+        let mut value = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk?;
+            if text.len() + value.len() + chunk.len() > limit {
+                return Err(ApiError::PayloadTooLarge(format!(
+                    "Files are limited to {} bytes",
+                    limit
+                )));
+            }
+            value.extend_from_slice(&chunk);
+        }
+
+        match name.as_str() {
+            "file" | "text" => text.extend_from_slice(&value),
+            "words" => {
+                let value = String::from_utf8_lossy(&value);
+                words.extend(
+                    value
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|word| !word.is_empty())
+                        .map(String::from),
+                );
+            }
+            // Anything else in the form is ignored
+            _ => {}
+        }
+    }
+
+    Ok((text, words))
+}
+
+#[utoipa::path(
+    tag = "wordcount",
+    request_body(content(
+        (WordCountRequest = "application/json"),
+        (WordCountForm = "multipart/form-data"),
+    )),
+    responses(
+        (status = 200, description = "How often each word occurs", body = WordCount),
+        (status = 400, description = "The text is not UTF-8, or the body is malformed", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "The text is too large", body = Problem, content_type = "application/problem+json"),
+        (status = 415, description = "Neither JSON nor a multipart form", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "No words, or too many", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+#[post("/wordcount")]
+pub async fn wordcount(
+    req: HttpRequest,
+    payload: web::Payload,
+    limit: web::Data<WordCountLimit>,
+) -> Result<web::Json<WordCount>, actix_web::Error> {
+    let (text, words) = match req.content_type() {
+        "application/json" => {
+            // The Json extractor, run by hand so it still uses the app's JsonConfig
+            let body = web::Json::<WordCountRequest>::from_request(&req, &mut payload.into_inner())
+                .await?
+                .into_inner();
+            (body.text.into_bytes(), body.words)
+        }
+        "multipart/form-data" => read_form(Multipart::new(req.headers(), payload), limit.0).await?,
+        other => {
+            return Err(ApiError::UnsupportedMediaType(format!(
+                "Expected application/json or multipart/form-data, not {:?}",
+                other
+            ))
+            .into())
+        }
+    };
+
+    if words.iter().all(|word| word.trim().is_empty()) {
+        return Err(words_error("must not be empty").into());
+    }
+    if words.len() > MAX_WORDS {
+        return Err(words_error("must have at most 100 words").into());
+    }
+
+    let counts = web::block(move || find_word_occurances(text.as_slice(), &words))
+        .await
+        .map_err(ApiError::from)?
+        .map_err(|e| ApiError::BadRequest(format!("The text must be UTF-8: {}", e)))?;
+
+    Ok(web::Json(WordCount {
+        counts: counts.into_iter().collect(),
+    }))
+}
@@ -0,0 +1,410 @@
+// Integration tests for the routes, running the real route table against an in-memory database
+use actix_web::http::StatusCode;
+use actix_web::test;
+use common::{bearer, test_app, test_state};
+use helloworld_web::auth::{JwtAuth, Role, Token};
+use helloworld_web::files::Upload;
+use helloworld_web::person::Person;
+use helloworld_web::settings::Settings;
+use helloworld_web::wordcount::WordCount;
+use helloworld_web::{app, AppState};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+#[actix_web::test]
+async fn index_serves_the_chat_client() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::get().uri("/").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "text/html; charset=utf-8"
+    );
+    let body = test::read_body(resp).await;
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.contains("Hello world!"));
+    assert!(body.contains("/ws?"));
+}
+
+#[actix_web::test]
+async fn echo_returns_the_body() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/echo")
+        .insert_header(bearer("guest", Role::Viewer))
+        .set_payload("ping")
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "ping");
+}
+
+#[actix_web::test]
+async fn hey() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::get().uri("/hey").to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "Hey there!");
+}
+
+#[actix_web::test]
+async fn people_crud() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/people")
+        .insert_header(bearer("bbc", Role::Editor))
+        .set_json(json!({"name": "BBC", "email": "bbc@demo.com"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(resp.headers().get("Location").unwrap(), "/people/1");
+    let created: Person = test::read_body_json(resp).await;
+    assert_eq!(created.name, "BBC");
+
+    let req = test::TestRequest::patch()
+        .uri("/people/1")
+        .insert_header(bearer("bbc", Role::Editor))
+        .set_json(json!({"email": "bbc@email.com"}))
+        .to_request();
+    let patched: Person = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.email, "bbc@email.com");
+
+    let req = test::TestRequest::put()
+        .uri("/people/1")
+        .insert_header(bearer("bbc", Role::Editor))
+        .set_json(json!({"name": "B", "email": "b@demo.com"}))
+        .to_request();
+    let replaced: Person = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(replaced.name, "B");
+
+    let req = test::TestRequest::get().uri("/people").to_request();
+    let people: Vec<Person> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(people, vec![replaced.clone()]);
+
+    let req = test::TestRequest::get().uri("/people/1").to_request();
+    let person: Person = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(person, replaced);
+
+    let req = test::TestRequest::delete()
+        .uri("/people/1")
+        .insert_header(bearer("bbc", Role::Editor))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get().uri("/people/1").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn invalid_people_get_problem_details() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/people")
+        .insert_header(bearer("bbc", Role::Editor))
+        .set_json(json!({"name": "", "email": "bbc"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "application/problem+json"
+    );
+    let problem: Value = test::read_body_json(resp).await;
+    assert_eq!(problem["errors"].as_array().unwrap().len(), 2);
+
+    let req = test::TestRequest::post()
+        .uri("/people")
+        .insert_header(bearer("bbc", Role::Editor))
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload("{\"name\":")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get().uri("/people/abc").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn login_issues_tokens_for_test_users() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/login")
+        .set_json(json!({"username": "guest", "password": "wrong"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/login")
+        .set_json(json!({"username": "guest", "password": "guest-password"}))
+        .to_request();
+    let token: Token = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(token.token_type, "Bearer");
+
+    let req = test::TestRequest::post()
+        .uri("/echo")
+        .insert_header(("Authorization", format!("Bearer {}", token.access_token)))
+        .set_payload("ping")
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "ping");
+}
+
+#[actix_web::test]
+async fn protected_routes_need_a_valid_token_and_role() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/echo")
+        .set_payload("ping")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(resp.headers().get("WWW-Authenticate").unwrap(), "Bearer");
+
+    // Signed with a different secret
+    let forged = JwtAuth::hs256(b"other-secret")
+        .issue("bbc", Role::Editor)
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/echo")
+        .insert_header(("Authorization", format!("Bearer {}", forged)))
+        .set_payload("ping")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/people")
+        .insert_header(bearer("guest", Role::Viewer))
+        .set_json(json!({"name": "BBC", "email": "bbc@demo.com"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Reading stays public
+    let req = test::TestRequest::get().uri("/people").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn readiness_fails_while_shutting_down() {
+    let state = test_state();
+    let readiness = state.readiness.clone();
+    let app = test::init_service(app(state)).await;
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    readiness.set_ready(false);
+
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // Still alive, just not taking new traffic
+    let req = test::TestRequest::get().uri("/healthz").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn uploads_are_streamed_to_disk_and_served_with_ranges() {
+    let dir = std::env::temp_dir().join(format!("helloworld-web-{}", std::process::id()));
+    let settings = Settings {
+        upload_dir: dir.clone(),
+        upload_limit: 16,
+        ..Settings::default()
+    };
+    let app = test::init_service(app(AppState {
+        settings,
+        ..test_state()
+    }))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/upload")
+        .insert_header(bearer("guest", Role::Viewer))
+        .set_payload("hello world")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let upload: Upload = test::read_body_json(resp).await;
+    assert_eq!(upload.size, 11);
+    assert_eq!(
+        upload.sha256,
+        "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/files/{}", upload.id))
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "hello world");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/files/{}", upload.id))
+        .insert_header(("Range", "bytes=6-"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(test::read_body(resp).await, "world");
+
+    let req = test::TestRequest::post()
+        .uri("/upload")
+        .insert_header(bearer("guest", Role::Viewer))
+        .set_payload("more than sixteen bytes")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let req = test::TestRequest::get()
+        .uri("/files/..%2Fpeople.db")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // Only the finished upload is left, nothing half written
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn wordcount_counts_json_text_and_uploaded_files() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::post()
+        .uri("/wordcount")
+        .set_json(json!({"text": "Travel, travel! Go.", "words": ["Travel", "stay"]}))
+        .to_request();
+    let count: WordCount = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(count.counts["travel"], 2);
+    assert_eq!(count.counts["stay"], 0);
+
+    let form = "--X\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"book.txt\"\r\n\
+        Content-Type: text/plain\r\n\r\n\
+        Lilliput is far\nvery far from Blefuscu, or is it\r\n\
+        --X\r\n\
+        Content-Disposition: form-data; name=\"words\"\r\n\r\n\
+        far,is lilliput\r\n\
+        --X--\r\n";
+    let req = test::TestRequest::post()
+        .uri("/wordcount")
+        .insert_header(("Content-Type", "multipart/form-data; boundary=X"))
+        .set_payload(form)
+        .to_request();
+    let count: WordCount = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(count.counts["far"], 2);
+    assert_eq!(count.counts["is"], 2);
+    assert_eq!(count.counts["lilliput"], 1);
+
+    let req = test::TestRequest::post()
+        .uri("/wordcount")
+        .set_json(json!({"text": "Travel", "words": []}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let req = test::TestRequest::post()
+        .uri("/wordcount")
+        .insert_header(("Content-Type", "text/plain"))
+        .set_payload("Travel")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[actix_web::test]
+async fn request_id_is_echoed_or_generated() {
+    let app = test::init_service(test_app()).await;
+
+    let req = test::TestRequest::get()
+        .uri("/hey")
+        .insert_header(("X-Request-Id", "from-the-caller-42"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.headers().get("X-Request-Id").unwrap(),
+        "from-the-caller-42"
+    );
+
+    // Two requests without one get two different ids
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let req = test::TestRequest::get().uri("/hey").to_request();
+        let resp = test::call_service(&app, req).await;
+        let id = resp.headers().get("X-Request-Id").unwrap();
+        ids.push(id.to_str().unwrap().to_string());
+    }
+    assert_eq!(ids[0].len(), 36, "{}", ids[0]);
+    assert_ne!(ids[0], ids[1]);
+
+    // An id that would mess up the logs is replaced too
+    let req = test::TestRequest::get()
+        .uri("/hey")
+        .insert_header(("X-Request-Id", "a".repeat(200)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Request-Id").unwrap().len(), 36);
+}
+
+// Collects what the JSON subscriber writes, one log line per entry
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[actix_web::test]
+async fn request_span_records_status_and_latency() {
+    let logs = Logs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_writer(move || writer.clone())
+        .finish();
+    // The test runtime is single threaded, so the subscriber sees the whole request
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = test::init_service(test_app()).await;
+    let req = test::TestRequest::get()
+        .uri("/hey")
+        .insert_header(("X-Request-Id", "latency-test"))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let finished: Value = logs
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|line| line["fields"]["message"] == "finished")
+        .unwrap_or_else(|| panic!("no finished event in {logs}"));
+
+    let span = &finished["span"];
+    assert_eq!(span["request_id"], "latency-test");
+    assert_eq!(span["status"], 200);
+    assert!(span["latency_ms"].as_f64().unwrap() >= 0.0, "{span}");
+}
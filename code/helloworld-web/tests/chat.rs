@@ -0,0 +1,70 @@
+// The chat over real WebSocket connections, against a server on a random port
+use awc::ws::{Frame, Message};
+use common::test_state;
+use futures_util::{SinkExt, StreamExt};
+use helloworld_web::app;
+use helloworld_web::chat::{ChatMessage, Kind};
+
+mod common;
+
+// Reads frames until the next chat message, skipping the server's pings
+async fn next_message<S>(client: &mut S) -> ChatMessage
+where
+    S: futures_util::Stream<Item = Result<Frame, awc::error::WsProtocolError>> + Unpin,
+{
+    loop {
+        match client.next().await.unwrap().unwrap() {
+            Frame::Text(json) => return serde_json::from_slice(&json).unwrap(),
+            Frame::Ping(_) | Frame::Pong(_) => continue,
+            frame => panic!("unexpected frame {:?}", frame),
+        }
+    }
+}
+
+#[actix_web::test]
+async fn messages_are_broadcast_to_the_room() {
+    // Every worker gets a clone of the same state, so they share one chat hub
+    let state = test_state();
+    let hub = state.chat.clone();
+    let server = actix_test::start(move || app(state.clone()));
+
+    let (_, mut alice) = awc::Client::new()
+        .ws(server.url("/ws?room=tea&name=alice"))
+        .connect()
+        .await
+        .unwrap();
+    let joined = next_message(&mut alice).await;
+    assert_eq!((joined.kind, joined.from.as_str()), (Kind::Join, "alice"));
+
+    let (_, mut bob) = awc::Client::new()
+        .ws(server.url("/ws?room=tea&name=bob"))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(next_message(&mut alice).await.from, "bob");
+    assert_eq!(next_message(&mut bob).await.kind, Kind::Join);
+
+    // Someone in another room doesn't hear the conversation
+    let (_, mut carol) = awc::Client::new()
+        .ws(server.url("/ws?room=coffee&name=carol"))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(next_message(&mut carol).await.from, "carol");
+
+    bob.send(Message::Text("hello".into())).await.unwrap();
+    for client in [&mut alice, &mut bob] {
+        let message = next_message(client).await;
+        assert_eq!(message.kind, Kind::Message);
+        assert_eq!(message.room, "tea");
+        assert_eq!(message.from, "bob");
+        assert_eq!(message.text, "hello");
+    }
+
+    // Closing the connection takes bob out of the room
+    bob.send(Message::Close(None)).await.unwrap();
+    let left = next_message(&mut alice).await;
+    assert_eq!((left.kind, left.from.as_str()), (Kind::Leave, "bob"));
+    assert_eq!(hub.members("tea"), 1);
+    assert_eq!(hub.members("coffee"), 1);
+}
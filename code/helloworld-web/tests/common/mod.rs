@@ -0,0 +1,42 @@
+// Shared by the integration tests. Each test file compiles this module on its own
+// and uses only part of it, hence the allow.
+#![allow(dead_code)]
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::App;
+use helloworld_web::auth::{JwtAuth, Role};
+use helloworld_web::repository::{PersonRepository, SqliteRepository};
+use helloworld_web::settings::Settings;
+use helloworld_web::{app, AppState};
+use std::sync::Arc;
+
+pub const SECRET: &[u8] = b"test-secret";
+
+// An empty in-memory database, HS256 tokens signed with SECRET and the default settings.
+// Tests that need something else change the fields they care about:
+//
+//     app(AppState { settings, ..test_state() })
+pub fn test_state() -> AppState {
+    let repository: Arc<dyn PersonRepository> = Arc::new(SqliteRepository::in_memory().unwrap());
+    AppState::new(repository, JwtAuth::hs256(SECRET), Settings::default())
+}
+
+// The app as main.rs runs it, on top of test_state()
+pub fn test_app() -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    app(test_state())
+}
+
+// Tokens signed with the same secret as the app's, without going through /login
+pub fn bearer(username: &str, role: Role) -> (&'static str, String) {
+    let token = JwtAuth::hs256(SECRET).issue(username, role).unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
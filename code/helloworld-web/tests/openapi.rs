@@ -0,0 +1,102 @@
+// Keeps /openapi.json in sync with the handlers: every route registered in src/ has
+// to be in the spec, and the spec can't describe routes that don't exist
+use actix_web::http::StatusCode;
+use actix_web::test::{call_and_read_body_json, call_service, init_service, TestRequest};
+use common::test_app;
+use helloworld_web::openapi::ApiDoc;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use utoipa::OpenApi;
+
+mod common;
+
+const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+// The first string literal in `s`, like `/people/{id}` in `("/people/{id}", wrap = ...)]`
+fn first_literal(s: &str) -> Option<&str> {
+    let start = s.find('"')? + 1;
+    let end = start + s[start..].find('"')?;
+    Some(&s[start..end])
+}
+
+// (method, path) of every `#[get("/path")]` style macro and `.route("/path", web::get()...)`
+fn routes_in_source(dir: &Path, routes: &mut BTreeSet<(String, String)>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            routes_in_source(&path, routes);
+            continue;
+        }
+        let source = fs::read_to_string(&path).unwrap();
+
+        for line in source.lines().map(str::trim) {
+            for method in METHODS {
+                if let Some(rest) = line.strip_prefix(&format!("#[{}(", method)) {
+                    let route = first_literal(rest).unwrap();
+                    routes.insert((method.to_string(), route.to_string()));
+                }
+            }
+            if let Some(rest) = line.strip_prefix(".route(") {
+                let route = first_literal(rest).unwrap();
+                let method = METHODS
+                    .iter()
+                    .find(|method| rest.contains(&format!("web::{}()", method)))
+                    .expect("a .route() with a web::<method>() guard");
+                routes.insert((method.to_string(), route.to_string()));
+            }
+        }
+    }
+}
+
+fn routes_in_spec() -> BTreeSet<(String, String)> {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut routes = BTreeSet::new();
+    for (path, item) in spec["paths"].as_object().unwrap() {
+        for method in METHODS {
+            if item.get(method).is_some() {
+                routes.insert((method.to_string(), path.clone()));
+            }
+        }
+    }
+    routes
+}
+
+#[test]
+fn every_route_is_in_the_spec() {
+    let mut in_source = BTreeSet::new();
+    routes_in_source(
+        Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src")),
+        &mut in_source,
+    );
+    assert!(!in_source.is_empty());
+
+    let in_spec = routes_in_spec();
+    let missing: Vec<_> = in_source.difference(&in_spec).collect();
+    assert!(
+        missing.is_empty(),
+        "routes without #[utoipa::path] or not listed in openapi::ApiDoc: {:?}",
+        missing
+    );
+    let stale: Vec<_> = in_spec.difference(&in_source).collect();
+    assert!(
+        stale.is_empty(),
+        "routes in the spec that don't exist: {:?}",
+        stale
+    );
+}
+
+#[actix_web::test]
+async fn spec_and_docs_are_served() {
+    let app = init_service(test_app()).await;
+
+    let req = TestRequest::get().uri("/openapi.json").to_request();
+    let spec: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(spec["info"]["title"], "helloworld-web");
+    assert!(spec["components"]["securitySchemes"]["bearer"].is_object());
+
+    let req = TestRequest::get().uri("/docs/").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,88 @@
+// Integration tests for the SQLite repository, mostly using an in-memory database
+use helloworld_web::person::{NewPerson, PersonPatch};
+use helloworld_web::repository::{PersonRepository, SqliteRepository};
+use rusqlite::Connection;
+use std::fs;
+use std::path::PathBuf;
+
+fn new_person(name: &str, email: &str) -> NewPerson {
+    NewPerson {
+        name: name.to_string(),
+        email: email.to_string(),
+    }
+}
+
+#[test]
+fn crud_round_trip() {
+    let repo = SqliteRepository::in_memory().unwrap();
+
+    let bbc = repo.insert(new_person("BBC", "bbc@demo.com")).unwrap();
+    let other = repo.insert(new_person("Other", "other@demo.com")).unwrap();
+    assert_eq!(repo.list().unwrap(), vec![bbc.clone(), other.clone()]);
+
+    let patch = PersonPatch {
+        email: Some("bbc@email.com".to_string()),
+        ..Default::default()
+    };
+    let patched = repo.update(bbc.id, patch).unwrap().unwrap();
+    assert_eq!(patched.name, "BBC");
+    assert_eq!(patched.email, "bbc@email.com");
+
+    assert!(repo.remove(other.id).unwrap());
+    assert!(!repo.remove(other.id).unwrap());
+    assert_eq!(repo.get(other.id).unwrap(), None);
+    assert_eq!(
+        repo.replace(other.id, new_person("X", "x@demo.com"))
+            .unwrap(),
+        None
+    );
+
+    // Ids of deleted people are not reused
+    let third = repo.insert(new_person("Third", "third@demo.com")).unwrap();
+    assert!(third.id > other.id);
+}
+
+// A directory of its own for each test's database file, removed again when the test is done.
+// SQLite puts its -wal and -shm files next to the database.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("helloworld-web-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn data_survives_reopening_the_file() {
+    let dir = TempDir::new("reopen");
+    let path = dir.0.join("people.db");
+
+    let repo = SqliteRepository::open(&path).unwrap();
+    let bbc = repo.insert(new_person("BBC", "bbc@demo.com")).unwrap();
+    drop(repo);
+
+    // The schema is already there, opening again must not run the migrations a second time
+    let repo = SqliteRepository::open(&path).unwrap();
+    assert_eq!(repo.list().unwrap(), vec![bbc]);
+    drop(repo);
+
+    let conn = Connection::open(&path).unwrap();
+    let version: u32 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, 1);
+    // WAL mode is stored in the file itself
+    let journal_mode: String = conn
+        .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(journal_mode, "wal");
+}
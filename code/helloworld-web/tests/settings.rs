@@ -0,0 +1,114 @@
+// Tests for loading the settings, and for how they are logged when the server starts
+use helloworld_web::settings::{Cli, Settings};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn debug_output_hides_the_jwt_secret() {
+    let settings = Settings {
+        jwt_secret: Some(String::from("hunter2")),
+        ..Settings::default()
+    };
+
+    let logged = format!("{settings:?}");
+    assert!(!logged.contains("hunter2"));
+    assert!(logged.contains(r#"jwt_secret: Some("***")"#));
+    assert!(logged.contains("port: 8080"));
+
+    let unset = format!("{:?}", Settings::default());
+    assert!(unset.contains("jwt_secret: None"));
+}
+
+// A TOML file of its own for each test, removed again when the test is done
+struct ConfigFile(PathBuf);
+
+impl ConfigFile {
+    fn new(name: &str, contents: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "helloworld-web-{}-{}.toml",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, contents).unwrap();
+        ConfigFile(path)
+    }
+}
+
+impl Drop for ConfigFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// Environment variables are shared by the whole test binary,
+// so this is the only test that sets any
+#[test]
+fn each_layer_overrides_the_ones_before_it() {
+    let file = ConfigFile::new(
+        "layers",
+        "port = 1000\nhost = \"file-host\"\nlog_level = \"warn\"\nworkers = 3\n",
+    );
+    std::env::set_var("APP_PORT", "2000");
+    std::env::set_var("APP_HOST", "env-host");
+
+    let mut cli = Cli {
+        config: Some(file.0.clone()),
+        port: Some(3000),
+        ..Cli::default()
+    };
+    let all_layers = Settings::load(&cli);
+
+    cli.port = None;
+    let without_cli = Settings::load(&cli);
+
+    std::env::remove_var("APP_PORT");
+    std::env::remove_var("APP_HOST");
+    let file_only = Settings::load(&cli);
+
+    let all_layers = all_layers.unwrap();
+    assert_eq!(all_layers.port, 3000);
+    assert_eq!(all_layers.host, "env-host");
+    assert_eq!(all_layers.log_level, "warn");
+    assert_eq!(all_layers.workers, 3);
+    // Set nowhere, so still the default
+    assert_eq!(all_layers.json_limit, Settings::default().json_limit);
+
+    let without_cli = without_cli.unwrap();
+    assert_eq!(
+        (without_cli.port, without_cli.host.as_str()),
+        (2000, "env-host")
+    );
+
+    let file_only = file_only.unwrap();
+    assert_eq!(
+        (file_only.port, file_only.host.as_str()),
+        (1000, "file-host")
+    );
+}
+
+#[test]
+fn missing_config_file_is_an_error_when_asked_for() {
+    let cli = Cli {
+        config: Some(PathBuf::from("does-not-exist.toml")),
+        ..Cli::default()
+    };
+    assert!(Settings::load(&cli).is_err());
+}
+
+// Actix panics when asked for 0 workers, load says what is wrong instead
+#[test]
+fn zero_workers_is_rejected() {
+    let cli = Cli {
+        workers: Some(0),
+        ..Cli::default()
+    };
+    let error = Settings::load(&cli).unwrap_err();
+    assert!(error.to_string().contains("workers"), "{error}");
+
+    let file = ConfigFile::new("zero-workers", "workers = 0\n");
+    let cli = Cli {
+        config: Some(file.0.clone()),
+        ..Cli::default()
+    };
+    assert!(Settings::load(&cli).is_err());
+}
@@ -0,0 +1,9 @@
+// Feed arbitrary bytes to the parser, it must return an error instead of panicking
+#![no_main]
+
+use http_server::http::Request;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Request::try_from(data);
+});
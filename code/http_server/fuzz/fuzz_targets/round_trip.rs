@@ -0,0 +1,13 @@
+// Whatever the parser accepts must serialize back to bytes that parse to the same request
+#![no_main]
+
+use http_server::http::Request;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request) = Request::try_from(data) {
+        let reparsed = Request::try_from(request.to_bytes().as_slice())
+            .expect("serialized request failed to parse");
+        assert_eq!(request, reparsed);
+    }
+});
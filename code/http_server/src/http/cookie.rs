@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+/* The browser sends all its cookies for our site in one header:
+
+Cookie: theme=dark; session=abc123\r\n
+
+and we ask it to store a cookie with one header per cookie:
+
+Set-Cookie: session=abc123; Path=/; Max-Age=3600; HttpOnly; Secure; SameSite=Lax\r\n
+
+*/
+
+// Splits a `Cookie` header value into (name, value) pairs.
+// The pairs borrow from the header, so no Strings are allocated.
+pub fn parse(header: &str) -> impl Iterator<Item = (&str, &str)> {
+    header.split(';').filter_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        // Values may optionally be wrapped in double quotes
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        Some((name, value))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl Display for SameSite {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Builder for a `Set-Cookie` header value.
+// Characters that would end the value or the header, like `;` or a newline,
+// are percent-encoded when it is written out, so they can't add attributes or headers.
+#[derive(Debug, Clone)]
+pub struct SetCookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    max_age: Option<Duration>,
+    http_only: bool,
+    secure: bool,
+    same_site: Option<SameSite>,
+}
+
+impl SetCookie {
+    pub fn new(name: &str, value: &str) -> Self {
+        SetCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            path: None,
+            max_age: None,
+            http_only: false,
+            secure: false,
+            same_site: None,
+        }
+    }
+
+    // A cookie with `Max-Age=0` tells the browser to delete it right away
+    pub fn removal(name: &str) -> Self {
+        SetCookie::new(name, "").max_age(Duration::ZERO)
+    }
+
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    // HttpOnly cookies are hidden from JavaScript running in the page
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    // Secure cookies are only sent back over HTTPS
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+}
+
+impl Display for SetCookie {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "{}={}",
+            encode(&self.name, b"="),
+            encode(&self.value, b"")
+        )?;
+
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", encode(path, b""))?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if self.http_only {
+            write!(f, "; HttpOnly")?;
+        }
+        if self.secure {
+            write!(f, "; Secure")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        Ok(())
+    }
+}
+
+// Percent-encodes everything that isn't allowed in a cookie value (RFC 6265):
+// control characters, spaces, `"`, `,`, `;`, `\` and non-ASCII bytes, plus `%` itself
+// so encoded values can be told apart, and any `extra` bytes
+fn encode<'a>(s: &'a str, extra: &[u8]) -> Cow<'a, str> {
+    let needs_encoding = |b: u8| {
+        b.is_ascii_control() || !b.is_ascii() || b" \",;\\%".contains(&b) || extra.contains(&b)
+    };
+
+    if !s.bytes().any(needs_encoding) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if needs_encoding(b) {
+            encoded.push_str(&format!("%{:02X}", b));
+        } else {
+            encoded.push(b as char);
+        }
+    }
+    Cow::Owned(encoded)
+}
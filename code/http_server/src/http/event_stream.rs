@@ -0,0 +1,134 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{ErrorKind, Result as IoResult, Write};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/* Server-Sent Events are a never ending `text/event-stream` body made of frames:
+
+id: 42\n
+event: log\n
+retry: 3000\n
+data: first line\n
+data: second line\n
+\n
+
+Lines starting with a colon are comments, which we use as heartbeats.
+*/
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    retry: Option<Duration>,
+    data: String,
+}
+
+impl Event {
+    pub fn new(data: &str) -> Self {
+        Event {
+            id: None,
+            event: None,
+            retry: None,
+            data: data.to_string(),
+        }
+    }
+
+    // The browser sends the last id it saw back in `Last-Event-ID` when it reconnects
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    // Named events are delivered to `addEventListener(name, ..)` instead of `onmessage`
+    pub fn event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_string());
+        self
+    }
+
+    // How long the browser should wait before reconnecting
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        // A newline inside a field would start a new field, so strip them from the single line fields
+        if let Some(id) = &self.id {
+            writeln!(f, "id: {}", id.replace(['\r', '\n'], ""))?;
+        }
+        if let Some(event) = &self.event {
+            writeln!(f, "event: {}", event.replace(['\r', '\n'], ""))?;
+        }
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry.as_millis())?;
+        }
+        // Multi line data is sent as one `data:` field per line. Browsers end a line at
+        // \r\n, \n and a lone \r, so all three are split on or the rest of the line
+        // would be read as a field of its own. Empty lines are kept, "a\n" is two lines.
+        for line in self.data.replace("\r\n", "\n").split(['\r', '\n']) {
+            match line {
+                "" => writeln!(f, "data:")?,
+                line => writeln!(f, "data: {}", line)?,
+            }
+        }
+        // The empty line dispatches the event
+        writeln!(f)
+    }
+}
+
+// The body of an event stream response.
+// Events come from the other end of an mpsc channel, usually owned by another thread.
+#[derive(Debug)]
+pub struct EventStream {
+    receiver: Receiver<Event>,
+    heartbeat: Duration,
+}
+
+impl EventStream {
+    pub fn new(receiver: Receiver<Event>) -> Self {
+        EventStream {
+            receiver,
+            heartbeat: Duration::from_secs(15),
+        }
+    }
+
+    // Proxies tend to close connections that stay quiet for too long
+    pub fn heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    // Writes events until every Sender is dropped or the client goes away.
+    // This blocks the calling thread for as long as the stream is open,
+    // which is why the server gives every stream a thread of its own.
+    pub fn write_to(&self, stream: &mut impl Write) -> IoResult<()> {
+        loop {
+            let frame = match self.receiver.recv_timeout(self.heartbeat) {
+                Ok(event) => event.to_string(),
+                Err(RecvTimeoutError::Timeout) => String::from(": heartbeat\n\n"),
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            };
+
+            if let Err(e) = stream
+                .write_all(frame.as_bytes())
+                .and_then(|_| stream.flush())
+            {
+                return match e.kind() {
+                    // The client closed the tab, that is the normal way for a stream to end
+                    ErrorKind::BrokenPipe
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted => Ok(()),
+                    _ => Err(e),
+                };
+            }
+        }
+    }
+}
+
+impl From<Receiver<Event>> for EventStream {
+    fn from(receiver: Receiver<Event>) -> Self {
+        EventStream::new(receiver)
+    }
+}
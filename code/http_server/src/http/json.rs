@@ -0,0 +1,100 @@
+// JSON bodies, only compiled with `--features json`
+
+use super::request::Request;
+use super::response::Response;
+use super::status_code::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+impl Request {
+    // Deserializes the body, after checking the client says it is sending JSON
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, JsonError> {
+        let is_json = self
+            .header("Content-Type")
+            .map(is_json_media_type)
+            .unwrap_or(false);
+        if !is_json {
+            return Err(JsonError::UnsupportedMediaType);
+        }
+
+        serde_json::from_slice(self.body()).map_err(JsonError::Malformed)
+    }
+}
+
+impl Response {
+    pub fn json<T: Serialize>(value: &T) -> Response {
+        json_response(StatusCode::Ok, value)
+    }
+}
+
+fn json_response<T: Serialize>(status_code: StatusCode, value: &T) -> Response {
+    match serde_json::to_string(value) {
+        Ok(body) => {
+            Response::new(status_code, Some(body)).with_header("Content-Type", "application/json")
+        }
+        // Only happens for types like maps with non-string keys, which is a bug in the handler
+        Err(e) => {
+            println!("Failed to serialize response: {}", e);
+            Response::new(StatusCode::InternalServerError, None)
+        }
+    }
+}
+
+// `application/json`, `application/json; charset=utf-8` or suffixed types like `application/problem+json`
+fn is_json_media_type(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or("").trim();
+    let media_type = media_type.to_ascii_lowercase();
+
+    media_type == "application/json"
+        || (media_type.starts_with("application/") && media_type.ends_with("+json"))
+}
+
+#[derive(Debug)]
+pub enum JsonError {
+    UnsupportedMediaType,
+    Malformed(serde_json::Error),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::UnsupportedMediaType => write!(f, "Expected Content-Type: application/json"),
+            Self::Malformed(e) => write!(f, "Malformed JSON body: {}", e),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+// Lets handlers write `Err(e) => return e.into()`
+impl From<JsonError> for Response {
+    fn from(e: JsonError) -> Self {
+        #[derive(Serialize)]
+        struct ErrorBody {
+            error: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            line: Option<usize>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            column: Option<usize>,
+        }
+
+        let (status_code, line, column) = match &e {
+            JsonError::UnsupportedMediaType => (StatusCode::UnsupportedMediaType, None, None),
+            JsonError::Malformed(inner) => (
+                StatusCode::BadRequest,
+                Some(inner.line()),
+                Some(inner.column()),
+            ),
+        };
+
+        let body = ErrorBody {
+            error: e.to_string(),
+            line,
+            column,
+        };
+
+        json_response(status_code, &body)
+    }
+}
@@ -1,4 +1,10 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
 // Define the valid HTTP methods as enum.
+// The variants are spelled the way they appear on the wire.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Method {
     GET,
     POST,
@@ -7,3 +13,30 @@ pub enum Method {
     HEAD,
     OPTIONS,
 }
+
+impl FromStr for Method {
+    type Err = MethodError;
+
+    // Method names are case-sensitive, so "get" is not a valid method
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GET" => Ok(Self::GET),
+            "POST" => Ok(Self::POST),
+            "PUT" => Ok(Self::PUT),
+            "DELETE" => Ok(Self::DELETE),
+            "HEAD" => Ok(Self::HEAD),
+            "OPTIONS" => Ok(Self::OPTIONS),
+            _ => Err(MethodError),
+        }
+    }
+}
+
+impl Display for Method {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        // The Debug output already matches the wire format
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug)]
+pub struct MethodError;
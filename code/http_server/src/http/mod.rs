@@ -1,7 +1,19 @@
+pub use cookie::{SameSite, SetCookie};
+pub use event_stream::{Event, EventStream};
+pub use headers::Headers;
+#[cfg(feature = "json")]
+pub use json::JsonError;
 pub use method::Method;
-pub use request::Request;
+pub use request::{ParseError, Request};
 pub use response::Response;
+pub use status_code::StatusCode;
 
+pub mod cookie;
+pub mod event_stream;
+pub mod headers;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod method;
 pub mod request;
 pub mod response;
+pub mod status_code;
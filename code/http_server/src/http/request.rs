@@ -1,4 +1,13 @@
-use super::method::Method;
+use super::cookie;
+use super::headers::Headers;
+use super::method::{Method, MethodError};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{self, Read};
+use std::str::{self, Utf8Error};
+
+// Requests bigger than this are rejected instead of being buffered in memory
+const MAX_REQUEST_SIZE: usize = 1024 * 1024;
 
 /* Request would look like this:
 
@@ -8,6 +17,7 @@ BODY
 
 */
 
+#[derive(Debug, PartialEq, Eq)]
 pub struct Request {
     path: String,
 
@@ -15,4 +25,296 @@ pub struct Request {
     // we will store Option:None
     query_string: Option<String>,
     method: Method,
+    headers: Headers,
+    body: Vec<u8>,
+}
+
+impl Request {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn query_string(&self) -> Option<&str> {
+        self.query_string.as_deref()
+    }
+
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)
+    }
+
+    // HTTP/1.1 requires exactly one Host header, None if it is missing, empty or sent twice
+    pub fn host(&self) -> Option<&str> {
+        let mut hosts = self.headers.get_all("Host");
+        match (hosts.next(), hosts.next()) {
+            (Some(host), None) if !host.is_empty() => Some(host),
+            _ => None,
+        }
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    // Browsers may split cookies over several `Cookie` headers, so look at all of them
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get_all("Cookie")
+            .flat_map(cookie::parse)
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    // The request as it would be sent on the wire, `Request::try_from` parses it back
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut target = self.path.clone();
+        if let Some(query_string) = &self.query_string {
+            target.push('?');
+            target.push_str(query_string);
+        }
+
+        let mut bytes = format!("{} {} HTTP/1.1\r\n", self.method, target).into_bytes();
+        for (name, value) in self.headers.iter() {
+            bytes.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        bytes.extend_from_slice(b"\r\n");
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    // Keep reading from the stream until we have a complete request.
+    // A single `read` call may return only part of what the client sent.
+    pub fn read_from(stream: &mut impl Read) -> Result<Self, ParseError> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut chunk = [0; 1024];
+
+        // Only the new bytes are searched for the empty line that ends the head,
+        // plus the last 3 before them in case a read split the \r\n\r\n
+        let head_end = loop {
+            let bytes_read = stream.read(&mut chunk)?;
+            if bytes_read == 0 {
+                // The client closed the connection before finishing the request
+                return Err(ParseError::Incomplete);
+            }
+            let search_from = buffer.len().saturating_sub(3);
+            buffer.extend_from_slice(&chunk[..bytes_read]);
+
+            if let Some(i) = find_subslice(&buffer[search_from..], b"\r\n\r\n") {
+                break search_from + i;
+            }
+            if buffer.len() >= MAX_REQUEST_SIZE {
+                return Err(ParseError::TooLarge);
+            }
+        };
+
+        // The head is parsed once, after that we know exactly how much body is left
+        let (mut request, content_length) = Request::parse_head(&buffer[..head_end])?;
+        let mut body = buffer.split_off(head_end + 4);
+        let received = body.len().min(content_length);
+        body.resize(content_length, 0);
+
+        stream
+            .read_exact(&mut body[received..])
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => ParseError::Incomplete,
+                _ => ParseError::from(e),
+            })?;
+        request.body = body;
+        Ok(request)
+    }
+
+    // The request line and headers, and the Content-Length they announce
+    fn parse_head(head: &[u8]) -> Result<(Self, usize), ParseError> {
+        let head = str::from_utf8(head)?;
+
+        let mut lines = head.split("\r\n");
+        let request_line = lines.next().ok_or(ParseError::InvalidRequest)?;
+
+        let mut parts = request_line.split(' ');
+        let (method, mut path, protocol) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(path), Some(protocol)) if parts.next().is_none() => {
+                (method, path, protocol)
+            }
+            _ => return Err(ParseError::InvalidRequest),
+        };
+
+        if path.is_empty() || path.contains(|c: char| c.is_control()) {
+            return Err(ParseError::InvalidRequest);
+        }
+
+        if protocol != "HTTP/1.1" {
+            return Err(ParseError::InvalidProtocol);
+        }
+
+        let method: Method = method.parse()?;
+
+        let mut query_string = None;
+        if let Some(i) = path.find('?') {
+            query_string = Some(path[i + 1..].to_string());
+            path = &path[..i];
+        }
+
+        let mut headers = Headers::new();
+        for line in lines {
+            let (name, value) = line.split_once(':').ok_or(ParseError::InvalidHeader)?;
+            if !is_token(name) {
+                return Err(ParseError::InvalidHeader);
+            }
+
+            // A stray \n or other control character could make a proxy in front of us
+            // see a different set of headers than we do
+            let value = value.trim_matches([' ', '\t']);
+            if value.contains(|c: char| c.is_control() && c != '\t') {
+                return Err(ParseError::InvalidHeader);
+            }
+            headers.append(name, value);
+        }
+
+        let content_length = content_length(&headers)?;
+        if content_length > MAX_REQUEST_SIZE {
+            return Err(ParseError::TooLarge);
+        }
+
+        let request = Self {
+            path: path.to_string(),
+            query_string,
+            method,
+            headers,
+            body: Vec::new(),
+        };
+        Ok((request, content_length))
+    }
+}
+
+impl TryFrom<&[u8]> for Request {
+    type Error = ParseError;
+
+    // GET /search?name=abc&sort=1 HTTP/1.1\r\n...HEADERS...\r\n\r\nBODY
+    fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
+        // The head ends at the first empty line; everything after it is the body
+        let head_end = find_subslice(buf, b"\r\n\r\n").ok_or(ParseError::Incomplete)?;
+        let (mut request, content_length) = Request::parse_head(&buf[..head_end])?;
+        let rest = &buf[head_end + 4..];
+
+        if rest.len() < content_length {
+            return Err(ParseError::Incomplete);
+        }
+        request.body = rest[..content_length].to_vec();
+        Ok(request)
+    }
+}
+
+// When two servers disagree on where a body ends, an attacker can hide a second request
+// inside the first one (request smuggling), so anything ambiguous is rejected outright
+fn content_length(headers: &Headers) -> Result<usize, ParseError> {
+    let mut lengths = headers.get_all("Content-Length");
+    let length = lengths.next();
+
+    if lengths.next().is_some() {
+        return Err(ParseError::ConflictingLength);
+    }
+
+    // We don't implement chunked bodies at all
+    if headers.get("Transfer-Encoding").is_some() {
+        return Err(match length {
+            Some(_) => ParseError::ConflictingLength,
+            None => ParseError::UnsupportedTransferEncoding,
+        });
+    }
+
+    match length {
+        // usize::from_str accepts a leading '+', which other servers don't, so check the digits ourselves
+        Some(value) if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+            value.parse().map_err(|_| ParseError::TooLarge)
+        }
+        Some(_) => Err(ParseError::InvalidHeader),
+        None => Ok(0),
+    }
+}
+
+// Header names are "tokens": visible ASCII without separators like spaces, quotes or colons
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
 }
+
+#[derive(Debug)]
+pub enum ParseError {
+    InvalidRequest,
+    InvalidEncoding,
+    InvalidProtocol,
+    InvalidMethod,
+    InvalidHeader,
+    ConflictingLength,
+    UnsupportedTransferEncoding,
+    Incomplete,
+    TooLarge,
+    // The client stopped sending before the request was complete
+    Timeout,
+    Io(io::Error),
+}
+
+impl ParseError {
+    fn message(&self) -> &str {
+        match self {
+            Self::InvalidRequest => "Invalid Request",
+            Self::InvalidEncoding => "Invalid Encoding",
+            Self::InvalidProtocol => "Invalid Protocol",
+            Self::InvalidMethod => "Invalid Method",
+            Self::InvalidHeader => "Invalid Header",
+            Self::ConflictingLength => "Conflicting Message Length",
+            Self::UnsupportedTransferEncoding => "Unsupported Transfer-Encoding",
+            Self::Incomplete => "Incomplete Request",
+            Self::TooLarge => "Request Too Large",
+            Self::Timeout => "Request Timeout",
+            Self::Io(_) => "I/O Error",
+        }
+    }
+}
+
+impl From<MethodError> for ParseError {
+    fn from(_: MethodError) -> Self {
+        Self::InvalidMethod
+    }
+}
+
+impl From<Utf8Error> for ParseError {
+    fn from(_: Utf8Error) -> Self {
+        Self::InvalidEncoding
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        // A read timeout shows up as WouldBlock on Unix and TimedOut on Windows
+        match e.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Self::Timeout,
+            _ => Self::Io(e),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Io(e) => write!(f, "{}: {}", self.message(), e),
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl Error for ParseError {}
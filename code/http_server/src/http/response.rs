@@ -1,4 +1,112 @@
+use super::cookie::SetCookie;
+use super::event_stream::EventStream;
+use super::headers::Headers;
+use super::status_code::StatusCode;
+use std::io::{Result as IoResult, Write};
+
+/* Response would look like this:
+
+HTTP/1.1 200 OK\r\n
+HEADERS \r\n
+BODY
+
+*/
+
+#[derive(Debug)]
 pub struct Response {
-    status_code: u32,
-    body: Option<String>,
+    status_code: StatusCode,
+    headers: Headers,
+    body: Body,
+}
+
+// Most responses have a body of known size,
+// event streams keep writing until the connection closes
+#[derive(Debug)]
+enum Body {
+    Fixed(Option<String>),
+    EventStream(EventStream),
+}
+
+impl Response {
+    pub fn new(status_code: StatusCode, body: Option<String>) -> Self {
+        Response {
+            status_code,
+            headers: Headers::new(),
+            body: Body::Fixed(body),
+        }
+    }
+
+    // Keeps the connection open and writes every event sent on the channel.
+    // Accepts a Receiver<Event>, or an EventStream with a custom heartbeat.
+    pub fn event_stream(events: impl Into<EventStream>) -> Self {
+        Response {
+            status_code: StatusCode::Ok,
+            headers: Headers::new(),
+            body: Body::EventStream(events.into()),
+        }
+        .with_header("Content-Type", "text/event-stream")
+        .with_header("Cache-Control", "no-cache")
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.status_code
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    // The server sends event streams from their own thread, they can stay open for hours
+    pub fn is_event_stream(&self) -> bool {
+        matches!(self.body, Body::EventStream(_))
+    }
+
+    // Builder style, so handlers can write `Response::new(..).with_header(..)`
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    // Each cookie needs its own `Set-Cookie` line, so these are appended
+    pub fn set_cookie(mut self, cookie: SetCookie) -> Self {
+        self.headers.append("Set-Cookie", &cookie.to_string());
+        self
+    }
+
+    // `impl Write` lets us send to a TcpStream, or to a Vec<u8> when debugging
+    pub fn send(&self, stream: &mut impl Write) -> IoResult<()> {
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\n",
+            self.status_code,
+            self.status_code.reason_phrase()
+        )?;
+        for (name, value) in self.headers.iter() {
+            write!(stream, "{}: {}\r\n", name, value)?;
+        }
+
+        match &self.body {
+            Body::Fixed(body) => {
+                let body = match body {
+                    Some(b) => b,
+                    None => "",
+                };
+
+                // We handle a single request per connection
+                write!(
+                    stream,
+                    "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )?;
+                stream.flush()
+            }
+            Body::EventStream(events) => {
+                // No Content-Length, the body ends when we close the connection
+                write!(stream, "Connection: close\r\n\r\n")?;
+                stream.flush()?;
+                events.write_to(stream)
+            }
+        }
+    }
 }
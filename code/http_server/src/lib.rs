@@ -0,0 +1,10 @@
+// The library crate holds the server itself, main.rs only wires up the demo site
+#![crate_name = "http_server"]
+
+pub mod http;
+pub mod metrics;
+pub mod rate_limit;
+pub mod router;
+pub mod server;
+pub mod session;
+mod thread_pool;
@@ -2,15 +2,40 @@
     This is a simple Http1.1 Server
 */
 
-use http::Method;
-use http::Request;
-use http::Response;
-use server::Server;
+use http_server::http::{Request, Response, StatusCode};
+use http_server::rate_limit::RateLimiter;
+use http_server::router::{Router, VirtualHosts};
+use http_server::server::Server;
+use http_server::session;
+use website_handler::WebsiteHandler;
 
-mod http;
-mod server;
+mod website_handler;
 
 fn main() {
-    let server = Server::new("127.0.0.1".to_string(), 8080);
-    server.run();
+    // Set SESSION_SECRET to keep sessions valid across restarts
+    let session_key = match std::env::var("SESSION_SECRET") {
+        Ok(secret) => secret.into_bytes(),
+        Err(_) => session::generate_key(),
+    };
+
+    // Try them with `curl -H "Host: status.localhost" localhost:8080/`
+    let status = Router::new().get("/", |_| {
+        Response::new(StatusCode::Ok, Some("All systems operational".to_string()))
+    });
+    let apps = Router::new().get("/", |request: &Request| {
+        let host = request.header("Host").unwrap_or_default();
+        Response::new(StatusCode::Ok, Some(format!("Welcome to {}", host)))
+    });
+
+    // Every other host name gets the main website
+    let hosts = VirtualHosts::new()
+        .host("status.localhost", status)
+        .host("*.apps.localhost", apps)
+        .default_host(WebsiteHandler::new(&session_key));
+
+    // Allow bursts of 20 requests, then 5 requests per second per client IP
+    let server = Server::new("127.0.0.1".to_string(), 8080)
+        .rate_limit(RateLimiter::new(20, 5.0))
+        .metrics("/metrics");
+    server.run(hosts);
 }
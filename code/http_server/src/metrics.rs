@@ -0,0 +1,235 @@
+/*
+    Server counters, rendered in the Prometheus text exposition format:
+
+    # HELP http_requests_total Requests handled, by method and status code.
+    # TYPE http_requests_total counter
+    http_requests_total{method="GET",status="200"} 12
+
+    Every worker thread updates the same Metrics, so everything is an atomic.
+*/
+
+use crate::http::{Method, StatusCode};
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::io::{Read, Result as IoResult, Write};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+// Upper bounds of the latency histogram buckets, in seconds
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+pub struct Metrics {
+    // New method/status pairs need the write lock once, after that a read lock is enough
+    requests: RwLock<HashMap<(Method, u16), AtomicU64>>,
+    in_flight: AtomicI64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    parse_errors: AtomicU64,
+    latency: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_request(&self, method: Method, status_code: StatusCode, latency: Duration) {
+        let key = (method, status_code as u16);
+
+        let requests = self.requests.read().unwrap();
+        match requests.get(&key) {
+            Some(counter) => {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+            None => {
+                // Release the read lock first, or taking the write lock would deadlock
+                drop(requests);
+                self.requests
+                    .write()
+                    .unwrap()
+                    .entry(key)
+                    .or_default()
+                    .fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        self.latency.observe(latency);
+    }
+
+    pub fn record_parse_error(&self) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Counts the connection as in flight until the returned guard is dropped
+    pub fn connection(&self) -> InFlight<'_> {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight { metrics: self }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        // Writing to a String can't fail, so the fmt::Results are ignored
+        let _ = writeln!(
+            out,
+            "# HELP http_requests_total Requests handled, by method and status code."
+        );
+        let _ = writeln!(out, "# TYPE http_requests_total counter");
+        let requests = self.requests.read().unwrap();
+        let mut keys: Vec<&(Method, u16)> = requests.keys().collect();
+        keys.sort_by_key(|(method, status)| (method.to_string(), *status));
+        for key in keys {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{method=\"{}\",status=\"{}\"}} {}",
+                key.0,
+                key.1,
+                requests[key].load(Ordering::Relaxed)
+            );
+        }
+
+        render_single(
+            &mut out,
+            "http_connections_in_flight",
+            "gauge",
+            "Connections currently being handled.",
+            self.in_flight.load(Ordering::Relaxed),
+        );
+        render_single(
+            &mut out,
+            "http_received_bytes_total",
+            "counter",
+            "Bytes read from clients.",
+            self.bytes_in.load(Ordering::Relaxed),
+        );
+        render_single(
+            &mut out,
+            "http_sent_bytes_total",
+            "counter",
+            "Bytes written to clients.",
+            self.bytes_out.load(Ordering::Relaxed),
+        );
+        render_single(
+            &mut out,
+            "http_parse_errors_total",
+            "counter",
+            "Requests that could not be parsed.",
+            self.parse_errors.load(Ordering::Relaxed),
+        );
+
+        self.latency.render(
+            &mut out,
+            "http_request_duration_seconds",
+            "Time from accepting a connection to finishing the response.",
+        );
+
+        out
+    }
+}
+
+fn render_single(out: &mut String, name: &str, kind: &str, help: &str, value: impl ToString) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value.to_string());
+}
+
+pub struct InFlight<'a> {
+    metrics: &'a Metrics,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.metrics.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    // One counter per bucket, the last one is +Inf
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    sum_micros: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, value: Duration) {
+        let seconds = value.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(value.as_micros() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+
+        // Prometheus buckets are cumulative, each one includes all the smaller ones
+        let mut cumulative = 0;
+        for (i, counter) in self.buckets.iter().enumerate() {
+            cumulative += counter.load(Ordering::Relaxed);
+            let bound = match LATENCY_BUCKETS.get(i) {
+                Some(bound) => bound.to_string(),
+                None => String::from("+Inf"),
+            };
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = writeln!(out, "{}_sum {}", name, sum);
+        let _ = writeln!(out, "{}_count {}", name, self.count.load(Ordering::Relaxed));
+    }
+}
+
+// Wraps a stream and adds every byte that goes through it to the metrics
+pub struct CountingStream<'a, S> {
+    inner: S,
+    metrics: &'a Metrics,
+}
+
+impl<'a, S> CountingStream<'a, S> {
+    pub fn new(inner: S, metrics: &'a Metrics) -> Self {
+        CountingStream { inner, metrics }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Read> Read for CountingStream<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        self.metrics
+            .bytes_in
+            .fetch_add(bytes_read as u64, Ordering::Relaxed);
+        Ok(bytes_read)
+    }
+}
+
+impl<S: Write> Write for CountingStream<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        let bytes_written = self.inner.write(buf)?;
+        self.metrics
+            .bytes_out
+            .fetch_add(bytes_written as u64, Ordering::Relaxed);
+        Ok(bytes_written)
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
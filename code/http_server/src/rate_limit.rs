@@ -0,0 +1,105 @@
+/*
+    Token bucket rate limiting, one bucket per client IP.
+
+    Every client starts with `burst` tokens and each request takes one.
+    Tokens come back at `refill_per_second`, up to `burst` again.
+    A client that runs out gets `429 Too Many Requests` with a `Retry-After` hint.
+*/
+
+use crate::http::{Response, StatusCode};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+pub struct RateLimiter {
+    burst: f64,
+    refill_per_second: f64,
+    idle_timeout: Duration,
+    // Shared by every worker thread, so the buckets live behind a Mutex
+    state: Mutex<State>,
+}
+
+struct State {
+    buckets: HashMap<IpAddr, Bucket>,
+    last_eviction: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(burst: u32, refill_per_second: f64) -> Self {
+        assert!(burst > 0, "burst must allow at least one request");
+        assert!(refill_per_second > 0.0, "refill rate must be positive");
+
+        RateLimiter {
+            burst: burst as f64,
+            refill_per_second,
+            idle_timeout: Duration::from_secs(60),
+            state: Mutex::new(State {
+                buckets: HashMap::new(),
+                last_eviction: Instant::now(),
+            }),
+        }
+    }
+
+    // Buckets of clients we haven't heard from for this long are dropped
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    // Takes a token for the client, or tells it how long to wait for the next one.
+    // The port is ignored, every connection from the same IP shares one bucket.
+    pub fn check(&self, peer: SocketAddr) -> Result<(), Duration> {
+        self.check_at(peer, Instant::now())
+    }
+
+    // `check` as if it was `now`, so tests can move the clock instead of sleeping
+    pub fn check_at(&self, peer: SocketAddr, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        // Eviction piggybacks on regular traffic instead of needing its own thread
+        if now.duration_since(state.last_eviction) >= self.idle_timeout {
+            let idle_timeout = self.idle_timeout;
+            state
+                .buckets
+                .retain(|_, bucket| now.duration_since(bucket.last_refill) < idle_timeout);
+            state.last_eviction = now;
+        }
+
+        let bucket = state.buckets.entry(peer.ip()).or_insert(Bucket {
+            tokens: self.burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_second))
+        }
+    }
+
+    // Clients that currently have a bucket, idle ones included until they are evicted
+    pub fn clients(&self) -> usize {
+        self.state.lock().unwrap().buckets.len()
+    }
+}
+
+// The answer for a client that has to wait `retry_after` for its next token
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    // Retry-After is in whole seconds, round up so clients don't come back too early
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    Response::new(StatusCode::TooManyRequests, None)
+        .with_header("Retry-After", &seconds.to_string())
+}
@@ -0,0 +1,168 @@
+/*
+    Routing requests to the code that handles them.
+
+    A Router picks a function by method and path:
+
+        Router::new()
+            .get("/", |_| Response::new(StatusCode::Ok, Some("Hello".to_string())))
+            .post("/login", login)
+
+    VirtualHosts picks a whole Handler by the Host header, so one Server can run several sites:
+
+        VirtualHosts::new()
+            .host("api.example.com", api)
+            .host("*.example.com", tenants)
+            .default_host(website)
+*/
+
+use crate::http::{Method, Request, Response, StatusCode};
+use crate::server::Handler;
+
+// Boxed so routes with different closures can live in the same Vec
+type Route = Box<dyn Fn(&Request) -> Response + Send + Sync>;
+
+#[derive(Default)]
+pub struct Router {
+    routes: Vec<(Method, String, Route)>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn route<F>(mut self, method: Method, path: &str, route: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.routes
+            .push((method, path.to_string(), Box::new(route)));
+        self
+    }
+
+    pub fn get<F>(self, path: &str, route: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.route(Method::GET, path, route)
+    }
+
+    pub fn post<F>(self, path: &str, route: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.route(Method::POST, path, route)
+    }
+}
+
+impl Handler for Router {
+    fn handle_request(&self, request: &Request) -> Response {
+        let mut allowed: Vec<String> = Vec::new();
+
+        for (method, path, route) in &self.routes {
+            if path != request.path() {
+                continue;
+            }
+            if *method == request.method() {
+                return route(request);
+            }
+            allowed.push(method.to_string());
+        }
+
+        // The path exists, just not for this method
+        if !allowed.is_empty() {
+            return Response::new(StatusCode::MethodNotAllowed, None)
+                .with_header("Allow", &allowed.join(", "));
+        }
+
+        Response::new(StatusCode::NotFound, None)
+    }
+}
+
+#[derive(Default)]
+pub struct VirtualHosts {
+    hosts: Vec<(String, Box<dyn Handler>)>,
+    wildcards: Vec<(String, Box<dyn Handler>)>,
+    default: Option<Box<dyn Handler>>,
+}
+
+impl VirtualHosts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `pattern` is either an exact host name, or `*.example.com` to match any of its subdomains
+    pub fn host(mut self, pattern: &str, handler: impl Handler) -> Self {
+        let pattern = pattern.to_ascii_lowercase();
+
+        match pattern.strip_prefix('*') {
+            // Keep the leading dot, so "*.example.com" does not match "badexample.com"
+            Some(suffix) => {
+                self.wildcards.push((suffix.to_string(), Box::new(handler)));
+                // The most specific wildcard wins, so keep the longest suffixes first
+                self.wildcards
+                    .sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+            }
+            None => self.hosts.push((pattern, Box::new(handler))),
+        }
+        self
+    }
+
+    // Handles requests whose Host doesn't match any of the other sites
+    pub fn default_host(mut self, handler: impl Handler) -> Self {
+        self.default = Some(Box::new(handler));
+        self
+    }
+
+    fn find(&self, host: &str) -> Option<&dyn Handler> {
+        let exact = self
+            .hosts
+            .iter()
+            .find(|(name, _)| name == host)
+            .map(|(_, handler)| handler);
+
+        let wildcard = || {
+            self.wildcards
+                .iter()
+                .find(|(suffix, _)| host.len() > suffix.len() && host.ends_with(suffix.as_str()))
+                .map(|(_, handler)| handler)
+        };
+
+        exact
+            .or_else(wildcard)
+            .or(self.default.as_ref())
+            .map(|handler| handler.as_ref())
+    }
+}
+
+impl Handler for VirtualHosts {
+    fn handle_request(&self, request: &Request) -> Response {
+        let host = match request.host() {
+            Some(host) => host,
+            None => return bad_host(),
+        };
+
+        match self.find(&host_name(host)) {
+            Some(handler) => handler.handle_request(request),
+            None => Response::new(StatusCode::NotFound, None),
+        }
+    }
+}
+
+// The server answers /metrics with this too, before the request gets here
+pub(crate) fn bad_host() -> Response {
+    Response::new(
+        StatusCode::BadRequest,
+        Some("Missing or duplicate Host header".to_string()),
+    )
+}
+
+// "Example.com:8080" -> "example.com", "[::1]:8080" -> "[::1]"
+fn host_name(host: &str) -> String {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    // A trailing dot means the same host name
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
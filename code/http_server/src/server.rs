@@ -1,13 +1,59 @@
 // Every file is it's own module
 // Everything inside a module is private by default
 
-use std::net::{TcpListener, TcpStream};
+use crate::http::{Method, ParseError, Request, Response, StatusCode};
+use crate::metrics::{CountingStream, Metrics};
+use crate::rate_limit::{too_many_requests, RateLimiter};
+use crate::router::bad_host;
+use crate::thread_pool::ThreadPool;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A handler turns a parsed request into a response.
+// It is shared by all the worker threads, so it has to be Send + Sync.
+pub trait Handler: Send + Sync + 'static {
+    fn handle_request(&self, request: &Request) -> Response;
+
+    // Default implementation, handlers can override it to customise error pages
+    fn handle_bad_request(&self, e: &ParseError) -> Response {
+        println!("Failed to parse request: {}", e);
+
+        let status_code = match e {
+            ParseError::TooLarge => StatusCode::PayloadTooLarge,
+            ParseError::Timeout => StatusCode::RequestTimeout,
+            _ => StatusCode::BadRequest,
+        };
+        Response::new(status_code, None)
+    }
+}
 
 // define a struct for the server
 pub struct Server {
     // struct definition
     addr: String,
     port: i32,
+    workers: usize,
+    rate_limiter: Option<RateLimiter>,
+    metrics_path: Option<String>,
+    max_streams: usize,
+    read_timeout: Duration,
+}
+
+// Everything a worker needs to handle a connection, shared between them with an Arc
+struct Context<H: Handler> {
+    handler: H,
+    rate_limiter: Option<RateLimiter>,
+    metrics: Metrics,
+    metrics_path: Option<String>,
+    max_streams: usize,
+    read_timeout: Duration,
+    // Event streams currently open, each on its own thread
+    open_streams: AtomicUsize,
 }
 
 impl Server {
@@ -15,30 +61,223 @@ impl Server {
 
     // methods - defined on the context of the struct (self)
     pub fn new(addr: String, port: i32) -> Self {
-        Self { addr, port }
+        Self {
+            addr,
+            port,
+            workers: 4,
+            rate_limiter: None,
+            metrics_path: None,
+            max_streams: 64,
+            read_timeout: Duration::from_secs(10),
+        }
     }
 
-    pub fn run(&self) {
-        // run will take the ownership of the self reference
-        println!(
-            "Server running at addr:{} and port:{}",
-            self.addr, self.port
-        );
+    // Number of threads handling connections
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    // Without a limiter a single noisy client can keep every worker busy
+    pub fn rate_limit(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    // Serves the server counters in Prometheus format on `GET <path>`, usually "/metrics"
+    pub fn metrics(mut self, path: &str) -> Self {
+        self.metrics_path = Some(path.to_string());
+        self
+    }
 
+    // Event streams get a thread each instead of a worker, so a few open streams
+    // can't starve everybody else. Past this many, new ones get a 503.
+    pub fn max_streams(mut self, max_streams: usize) -> Self {
+        self.max_streams = max_streams;
+        self
+    }
+
+    // How long a client gets to send its request. Without a limit, a client that
+    // connects and sends nothing would hold on to a worker for good.
+    pub fn read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    // Binds the listening socket, without handling any connections yet.
+    // With port 0 the OS picks a free port, `local_addr` says which one.
+    pub fn bind(self) -> io::Result<BoundServer> {
         let server_addr = format!("{}:{}", self.addr, self.port);
+        let listener = TcpListener::bind(server_addr)?;
+        Ok(BoundServer {
+            server: self,
+            listener,
+        })
+    }
 
+    pub fn run(self, handler: impl Handler) {
         // we use unwrap to get the value from the Result<T> enum
         // In case of an Err, we Panic and terminate the program
-        let listener = TcpListener::bind(server_addr).unwrap();
+        self.bind().unwrap().run(handler)
+    }
+
+    // functions - These are like static functions, they do not need an instance of the struct
+    fn handle_client<H: Handler>(context: Arc<Context<H>>, stream: TcpStream) {
+        let started = Instant::now();
+        let metrics = &context.metrics;
+        let _in_flight = metrics.connection();
+
+        if let Err(e) = stream.set_read_timeout(Some(context.read_timeout)) {
+            println!("Failed to set the read timeout: {}", e);
+        }
+        let mut stream = CountingStream::new(stream, metrics);
+
+        // Read the request even when the client is over its limit,
+        // closing a socket with unread data can reset the connection before the 429 arrives
+        let request = Request::read_from(&mut stream);
+
+        let limited = match (&context.rate_limiter, stream.get_ref().peer_addr()) {
+            (Some(rate_limiter), Ok(peer)) => rate_limiter.check(peer).err(),
+            _ => None,
+        };
+
+        let (method, response) = match (limited, request) {
+            (Some(retry_after), Ok(request)) => {
+                (Some(request.method()), too_many_requests(retry_after))
+            }
+            (Some(retry_after), Err(_)) => {
+                metrics.record_parse_error();
+                (None, too_many_requests(retry_after))
+            }
+            (None, Ok(request)) => (Some(request.method()), Self::route(&context, &request)),
+            (None, Err(e)) => {
+                metrics.record_parse_error();
+                (None, context.handler.handle_bad_request(&e))
+            }
+        };
+
+        // An event stream is handed to a thread of its own, and the worker moves on.
+        // Its latency is the time until it started, how long the client listens doesn't count.
+        let response = if response.is_event_stream() {
+            match OpenStream::open(&context) {
+                Some(open_stream) => {
+                    if let Some(method) = method {
+                        metrics.record_request(method, response.status_code(), started.elapsed());
+                    }
+                    let stream = stream.into_inner();
+                    thread::spawn(move || open_stream.send(stream, response));
+                    return;
+                }
+                // Dropping the stream drops its receiver, which tells the producer to stop
+                None => Response::new(StatusCode::ServiceUnavailable, None)
+                    .with_header("Retry-After", "5"),
+            }
+        } else {
+            response
+        };
+
+        if let Err(e) = response.send(&mut stream) {
+            println!("Failed to send response: {}", e);
+        }
+
+        if let Some(method) = method {
+            metrics.record_request(method, response.status_code(), started.elapsed());
+        }
+    }
+
+    fn route<H: Handler>(context: &Context<H>, request: &Request) -> Response {
+        match &context.metrics_path {
+            // The metrics are held to the same Host rules as the sites behind VirtualHosts
+            Some(path) if request.path() == path && request.host().is_none() => bad_host(),
+            Some(path) if request.method() == Method::GET && request.path() == path => {
+                Response::new(StatusCode::Ok, Some(context.metrics.render()))
+                    .with_header("Content-Type", "text/plain; version=0.0.4")
+            }
+            // A panicking handler gets a 500, and the worker lives on to serve the next connection
+            _ => panic::catch_unwind(AssertUnwindSafe(|| context.handler.handle_request(request)))
+                .unwrap_or_else(|_| Response::new(StatusCode::InternalServerError, None)),
+        }
+    }
+}
+
+// A server with its socket bound, ready to run
+pub struct BoundServer {
+    server: Server,
+    listener: TcpListener,
+}
+
+impl BoundServer {
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn run(self, handler: impl Handler) {
+        let server = self.server;
+        // With port 0, the address we bound to tells us which port we got
+        let addr = self.listener.local_addr().map_or_else(
+            |_| format!("{}:{}", server.addr, server.port),
+            |addr| addr.to_string(),
+        );
+        println!("Server running at {} with {} workers", addr, server.workers);
+
+        // Every worker needs access to the handler, so we share it with an Arc
+        let context = Arc::new(Context {
+            handler,
+            rate_limiter: server.rate_limiter,
+            metrics: Metrics::new(),
+            metrics_path: server.metrics_path,
+            max_streams: server.max_streams,
+            read_timeout: server.read_timeout,
+            open_streams: AtomicUsize::new(0),
+        });
+        let pool = ThreadPool::new(server.workers);
 
         println!("Press Ctrl-C to exit...");
-        // start an infinite loop
-        loop {
-            listener.accept();
+        // incoming() is an infinite iterator over the connections
+        for stream in self.listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let context = Arc::clone(&context);
+                    pool.execute(move || Server::handle_client(context, stream));
+                }
+                Err(e) => println!("Failed to establish a connection: {}", e),
+            }
         }
     }
+}
+
+// Holds one of the `max_streams` places until the stream is done with it
+struct OpenStream<H: Handler> {
+    context: Arc<Context<H>>,
+}
+
+impl<H: Handler> OpenStream<H> {
+    fn open(context: &Arc<Context<H>>) -> Option<Self> {
+        context
+            .open_streams
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < context.max_streams).then_some(open + 1)
+            })
+            .ok()?;
+
+        Some(OpenStream {
+            context: Arc::clone(context),
+        })
+    }
 
-    fn handle_client(&self, stream: TcpStream) {
-        // ...
-    } // functions - These are like static functions, they do not need an instance of the struct
+    fn send(self, stream: TcpStream, response: Response) {
+        let metrics = &self.context.metrics;
+        let _in_flight = metrics.connection();
+        let mut stream = CountingStream::new(stream, metrics);
+
+        if let Err(e) = response.send(&mut stream) {
+            println!("Failed to send event stream: {}", e);
+        }
+    }
+}
+
+impl<H: Handler> Drop for OpenStream<H> {
+    fn drop(&mut self) {
+        self.context.open_streams.fetch_sub(1, Ordering::AcqRel);
+    }
 }
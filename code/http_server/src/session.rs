@@ -0,0 +1,230 @@
+/*
+    Cookie based sessions.
+
+    The browser only ever sees a random session id plus an HMAC signature:
+
+        Set-Cookie: session=3f2a...9c.8e01...d4; Path=/; HttpOnly; SameSite=Lax
+
+    The session data itself stays in a SessionStore on the server.
+    The signature lets us reject forged or tampered ids without a store lookup.
+*/
+
+use crate::http::{Request, SameSite, SetCookie};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// The data we keep for a logged in client
+pub type Session = HashMap<String, String>;
+
+// Anything that can keep sessions between requests.
+// The store is shared by every worker thread, hence Send + Sync.
+pub trait SessionStore: Send + Sync {
+    fn load(&self, id: &str) -> Option<Session>;
+    fn save(&self, id: &str, session: Session, ttl: Duration);
+    fn destroy(&self, id: &str);
+}
+
+// Sessions kept in a HashMap, they are lost when the server restarts
+#[derive(Default)]
+pub struct MemorySessionStore {
+    // The Mutex gives us interior mutability, so the trait methods only need &self
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    sessions: HashMap<String, (Session, Instant)>,
+    // The map is swept for expired sessions when it grows to this size
+    sweep_at: usize,
+}
+
+// Below this many sessions the map is never swept
+const MIN_SWEEP_AT: usize = 64;
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Includes expired sessions that haven't been swept yet
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load(&self, id: &str) -> Option<Session> {
+        let sessions = &mut self.entries.lock().unwrap().sessions;
+
+        match sessions.get(id) {
+            Some((session, expires_at)) if *expires_at > Instant::now() => Some(session.clone()),
+            Some(_) => {
+                // Expired sessions are removed when they are looked up, or by the next sweep
+                sessions.remove(id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn save(&self, id: &str, session: Session, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries
+            .sessions
+            .insert(id.to_string(), (session, now + ttl));
+
+        // Sessions that are never looked up again would otherwise stay forever.
+        // Sweeping each time the map doubles in size keeps saving O(1) on average.
+        if entries.sessions.len() >= entries.sweep_at {
+            entries
+                .sessions
+                .retain(|_, (_, expires_at)| *expires_at > now);
+            entries.sweep_at = (entries.sessions.len() * 2).max(MIN_SWEEP_AT);
+        }
+    }
+
+    fn destroy(&self, id: &str) {
+        self.entries.lock().unwrap().sessions.remove(id);
+    }
+}
+
+// Connects the session cookie to a SessionStore
+pub struct SessionManager<S: SessionStore> {
+    store: S,
+    key: Vec<u8>,
+    cookie_name: String,
+    max_age: Duration,
+    secure: bool,
+}
+
+impl<S: SessionStore> SessionManager<S> {
+    pub fn new(store: S, key: &[u8]) -> Self {
+        SessionManager {
+            store,
+            key: key.to_vec(),
+            cookie_name: String::from("session"),
+            max_age: Duration::from_secs(60 * 60),
+            secure: false,
+        }
+    }
+
+    pub fn cookie_name(mut self, cookie_name: &str) -> Self {
+        self.cookie_name = cookie_name.to_string();
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    // Turn this on when the server sits behind HTTPS
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    // Returns the session id and data, if the request carries a valid session cookie
+    pub fn load(&self, request: &Request) -> Option<(String, Session)> {
+        let id = self.session_id(request)?;
+        let session = self.store.load(id)?;
+        Some((id.to_string(), session))
+    }
+
+    // Stores a new session and returns the cookie that must be sent to the client
+    pub fn start(&self, session: Session) -> SetCookie {
+        let id = generate_id();
+        self.store.save(&id, session, self.max_age);
+
+        SetCookie::new(&self.cookie_name, &self.sign(&id))
+            .path("/")
+            .max_age(self.max_age)
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(SameSite::Lax)
+    }
+
+    pub fn save(&self, id: &str, session: Session) {
+        self.store.save(id, session, self.max_age);
+    }
+
+    // Forgets the session and returns a cookie that removes it from the browser
+    pub fn end(&self, request: &Request) -> SetCookie {
+        if let Some(id) = self.session_id(request) {
+            self.store.destroy(id);
+        }
+
+        SetCookie::removal(&self.cookie_name)
+            .path("/")
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(SameSite::Lax)
+    }
+
+    fn session_id<'a>(&self, request: &'a Request) -> Option<&'a str> {
+        let value = request.cookie(&self.cookie_name)?;
+        self.verify(value)
+    }
+
+    // "<id>.<hex encoded HMAC of id>"
+    fn sign(&self, id: &str) -> String {
+        let mut mac = self.mac();
+        mac.update(id.as_bytes());
+        let signature = mac.finalize().into_bytes();
+
+        format!("{}.{}", id, to_hex(&signature))
+    }
+
+    fn verify<'a>(&self, value: &'a str) -> Option<&'a str> {
+        let (id, signature) = value.rsplit_once('.')?;
+        let signature = from_hex(signature)?;
+
+        let mut mac = self.mac();
+        mac.update(id.as_bytes());
+        // verify_slice compares in constant time, so the signature can't be guessed byte by byte
+        mac.verify_slice(&signature).ok()?;
+        Some(id)
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any size")
+    }
+}
+
+// A random 256 bit key, for servers that don't need sessions to outlive the process
+pub fn generate_key() -> Vec<u8> {
+    let mut key = vec![0; 32];
+    getrandom::getrandom(&mut key).expect("failed to read from the OS random source");
+    key
+}
+
+fn generate_id() -> String {
+    let mut id = [0; 16];
+    getrandom::getrandom(&mut id).expect("failed to read from the OS random source");
+    to_hex(&id)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,83 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+// A job is any closure that can be sent to another thread and run once
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+// A fixed number of worker threads, all pulling jobs off the same channel.
+// Spawning a new thread for every connection would let a flood of clients exhaust the machine.
+pub struct ThreadPool {
+    workers: Vec<Worker>,
+    sender: Option<Sender<Job>>,
+}
+
+impl ThreadPool {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "a thread pool needs at least one worker");
+
+        let (sender, receiver): (Sender<Job>, Receiver<Job>) = channel();
+
+        // mpsc only allows a single consumer, so the workers share the receiver behind a Mutex
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..size)
+            .map(|id| Worker::new(id, Arc::clone(&receiver)))
+            .collect();
+
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+        }
+    }
+
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if let Some(sender) = &self.sender {
+            // This only fails if every worker has panicked and dropped the receiver
+            if sender.send(Box::new(job)).is_err() {
+                println!("Thread pool has no workers left to run the job");
+            }
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Dropping the sender closes the channel, which ends the loop in every worker
+        drop(self.sender.take());
+
+        for worker in self.workers.drain(..) {
+            if let Some(thread) = worker.thread {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+struct Worker {
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, receiver: Arc<Mutex<Receiver<Job>>>) -> Self {
+        let thread = thread::Builder::new()
+            .name(format!("worker-{}", id))
+            .spawn(move || loop {
+                // The lock guard is a temporary, so it is released before the job runs
+                let message = receiver.lock().unwrap().recv();
+
+                match message {
+                    Ok(job) => job(),
+                    Err(_) => break,
+                }
+            })
+            .expect("failed to spawn worker thread");
+
+        Worker {
+            thread: Some(thread),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use http_server::http::{Event, Method, Request, Response, StatusCode};
+use http_server::server::Handler;
+use http_server::session::{MemorySessionStore, Session, SessionManager};
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+// Lines sent by the demo log feed before it ends, one a second
+const LOG_LINES: u32 = 60;
+
+// A small demo site with a login flow:
+//
+//   curl -i -c jar -d "username=bbc" localhost:8080/login
+//   curl -i -b jar localhost:8080/whoami
+//   curl -i -b jar -X POST localhost:8080/logout
+//
+// and a live log feed at `curl -N localhost:8080/logs`
+//
+// With `cargo run --features json` there is also a JSON endpoint:
+//
+//   curl -i -H "Content-Type: application/json" -d '{"name":"bbc"}' localhost:8080/api/greet
+pub struct WebsiteHandler {
+    sessions: SessionManager<MemorySessionStore>,
+}
+
+impl WebsiteHandler {
+    pub fn new(session_key: &[u8]) -> Self {
+        WebsiteHandler {
+            sessions: SessionManager::new(MemorySessionStore::new(), session_key),
+        }
+    }
+
+    fn login(&self, request: &Request) -> Response {
+        let username = match form_value(request.body(), "username") {
+            Some(username) if !username.is_empty() => username,
+            _ => {
+                return Response::new(StatusCode::BadRequest, Some("Missing username".to_string()))
+            }
+        };
+
+        let mut session = Session::new();
+        session.insert("username".to_string(), username.to_string());
+
+        let cookie = self.sessions.start(session);
+        Response::new(StatusCode::Ok, Some(format!("Welcome {}", username))).set_cookie(cookie)
+    }
+
+    fn whoami(&self, request: &Request) -> Response {
+        match self.sessions.load(request) {
+            Some((_, session)) => {
+                let username = session.get("username").map_or("", String::as_str);
+                Response::new(StatusCode::Ok, Some(username.to_string()))
+            }
+            None => Response::new(StatusCode::Unauthorized, Some("Not logged in".to_string())),
+        }
+    }
+
+    fn logout(&self, request: &Request) -> Response {
+        let cookie = self.sessions.end(request);
+        Response::new(StatusCode::Ok, Some("Logged out".to_string())).set_cookie(cookie)
+    }
+
+    fn logs(&self) -> Response {
+        let (tx, rx) = channel();
+
+        // The producer runs on its own thread. It stops as soon as send fails, which
+        // happens once the client has gone and the response dropped the receiver,
+        // and otherwise after LOG_LINES lines, which ends the stream.
+        thread::spawn(move || {
+            for line in 1..=LOG_LINES {
+                let event = Event::new(&format!("log line {}", line))
+                    .id(&line.to_string())
+                    .event("log");
+                if tx.send(event).is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+        });
+
+        Response::event_stream(rx)
+    }
+
+    #[cfg(feature = "json")]
+    fn greet(&self, request: &Request) -> Response {
+        #[derive(Deserialize)]
+        struct Greet {
+            name: String,
+        }
+
+        #[derive(Serialize)]
+        struct Greeting {
+            greeting: String,
+        }
+
+        match request.json::<Greet>() {
+            Ok(greet) => Response::json(&Greeting {
+                greeting: format!("Hello {}!", greet.name),
+            }),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl Handler for WebsiteHandler {
+    fn handle_request(&self, request: &Request) -> Response {
+        match (request.method(), request.path()) {
+            (Method::GET, "/") => Response::new(StatusCode::Ok, Some("Hello world!".to_string())),
+            (Method::POST, "/login") => self.login(request),
+            (Method::GET, "/whoami") => self.whoami(request),
+            (Method::POST, "/logout") => self.logout(request),
+            (Method::GET, "/logs") => self.logs(),
+            #[cfg(feature = "json")]
+            (Method::POST, "/api/greet") => self.greet(request),
+            _ => Response::new(StatusCode::NotFound, None),
+        }
+    }
+}
+
+// Looks up a field in an `application/x-www-form-urlencoded` body like `username=bbc&remember=1`
+fn form_value<'a>(body: &'a [u8], name: &str) -> Option<&'a str> {
+    let body = std::str::from_utf8(body).ok()?;
+
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
@@ -0,0 +1,44 @@
+// Shared by the tests that talk to a real server. Each test file compiles this module
+// on its own and uses only part of it, hence the allow.
+#![allow(dead_code)]
+
+use http_server::server::{Handler, Server};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+// Nothing a test sends should take anywhere near this long
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// A server on a free port picked by the OS, configure it further before spawning it
+pub fn local_server() -> Server {
+    Server::new("127.0.0.1".to_string(), 0)
+}
+
+// Runs the server on a thread of its own. The socket is bound before this returns,
+// so a bind failure fails the test here and clients can connect straight away.
+pub fn spawn_server(server: Server, handler: impl Handler) -> SocketAddr {
+    let server = server.bind().expect("failed to bind the test server");
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run(handler));
+    addr
+}
+
+// Sends a raw request and reads the response until the server closes the connection
+pub fn send(addr: SocketAddr, raw: &str) -> String {
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT).unwrap();
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+    stream.write_all(raw.as_bytes()).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+pub fn get(addr: SocketAddr, path: &str) -> String {
+    send(
+        addr,
+        &format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path),
+    )
+}
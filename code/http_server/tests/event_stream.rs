@@ -0,0 +1,155 @@
+// Tests for Server-Sent Events: the frames, the heartbeats, and a client going away
+use http_server::http::{Event, EventStream, Response};
+use std::io::{Error, ErrorKind, Result as IoResult, Write};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn frames_every_field() {
+    let event = Event::new("first line\nsecond line")
+        .id("42")
+        .event("log")
+        .retry(Duration::from_secs(3));
+
+    assert_eq!(
+        event.to_string(),
+        "id: 42\nevent: log\nretry: 3000\ndata: first line\ndata: second line\n\n"
+    );
+    assert_eq!(Event::new("").to_string(), "data:\n\n");
+}
+
+// A newline in the id or the name would start a field of its own
+#[test]
+fn single_line_fields_drop_newlines() {
+    let event = Event::new("x").id("1\r\ndata: injected").event("a\nb");
+    assert_eq!(
+        event.to_string(),
+        "id: 1data: injected\nevent: ab\ndata: x\n\n"
+    );
+}
+
+// A lone \r ends a line for the browser too, so it can't smuggle in fields either
+#[test]
+fn data_splits_on_every_line_ending() {
+    assert_eq!(
+        Event::new("x\revent: admin\rdata: y").to_string(),
+        "data: x\ndata: event: admin\ndata: data: y\n\n"
+    );
+    assert_eq!(
+        Event::new("a\r\nb\nc\rd").to_string(),
+        "data: a\ndata: b\ndata: c\ndata: d\n\n"
+    );
+}
+
+// Trailing and doubled newlines are part of the data, each one is an empty `data:` line
+#[test]
+fn data_keeps_empty_lines() {
+    assert_eq!(Event::new("a\n").to_string(), "data: a\ndata:\n\n");
+    assert_eq!(
+        Event::new("a\r\n\r\n").to_string(),
+        "data: a\ndata:\ndata:\n\n"
+    );
+    assert_eq!(Event::new("\nb").to_string(), "data:\ndata: b\n\n");
+}
+
+#[test]
+fn sends_events_until_the_senders_are_dropped() {
+    let (tx, rx) = channel();
+    tx.send(Event::new("one").id("1")).unwrap();
+    tx.send(Event::new("two").id("2")).unwrap();
+    drop(tx);
+
+    let mut body = Vec::new();
+    EventStream::new(rx).write_to(&mut body).unwrap();
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "id: 1\ndata: one\n\nid: 2\ndata: two\n\n"
+    );
+}
+
+#[test]
+fn sends_heartbeats_while_nothing_happens() {
+    let (tx, rx) = channel();
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        tx.send(Event::new("late")).unwrap();
+    });
+
+    let mut body = Vec::new();
+    EventStream::new(rx)
+        .heartbeat(Duration::from_millis(20))
+        .write_to(&mut body)
+        .unwrap();
+    producer.join().unwrap();
+
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.starts_with(": heartbeat\n\n"));
+    assert!(body.ends_with("data: late\n\n"));
+}
+
+// Accepts a number of writes, then fails every one after that with `kind`
+struct Client {
+    writes_left: usize,
+    kind: ErrorKind,
+}
+
+impl Write for Client {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        if self.writes_left == 0 {
+            return Err(Error::from(self.kind));
+        }
+        self.writes_left -= 1;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        Ok(())
+    }
+}
+
+// A client closing the connection is how a stream normally ends,
+// and the producer finds out from the next send failing
+#[test]
+fn client_going_away_ends_the_stream() {
+    let (tx, rx) = channel();
+    tx.send(Event::new("one")).unwrap();
+    tx.send(Event::new("two")).unwrap();
+
+    let mut client = Client {
+        writes_left: 1,
+        kind: ErrorKind::BrokenPipe,
+    };
+    let stream = EventStream::new(rx);
+    assert!(stream.write_to(&mut client).is_ok());
+
+    drop(stream);
+    assert!(tx.send(Event::new("three")).is_err());
+}
+
+#[test]
+fn other_write_errors_are_returned() {
+    let (tx, rx) = channel();
+    tx.send(Event::new("one")).unwrap();
+
+    let mut client = Client {
+        writes_left: 0,
+        kind: ErrorKind::OutOfMemory,
+    };
+    let error = EventStream::new(rx).write_to(&mut client).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::OutOfMemory);
+}
+
+#[test]
+fn response_headers() {
+    let (_tx, rx) = channel::<Event>();
+    let response = Response::event_stream(rx);
+
+    assert!(response.is_event_stream());
+    assert_eq!(
+        response.headers().get("Content-Type"),
+        Some("text/event-stream")
+    );
+    assert_eq!(response.headers().get("Cache-Control"), Some("no-cache"));
+    assert!(!Response::new(response.status_code(), None).is_event_stream());
+}
@@ -0,0 +1,112 @@
+// Tests for JSON bodies, run them with `cargo test --features json`
+#![cfg(feature = "json")]
+
+use http_server::http::{JsonError, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct Greet {
+    name: String,
+}
+
+fn post(content_type: Option<&str>, body: &str) -> Request {
+    let content_type = content_type
+        .map(|value| format!("Content-Type: {}\r\n", value))
+        .unwrap_or_default();
+    let raw = format!(
+        "POST /api/greet HTTP/1.1\r\n{}Content-Length: {}\r\n\r\n{}",
+        content_type,
+        body.len(),
+        body
+    );
+    Request::try_from(raw.as_bytes()).unwrap()
+}
+
+// The status code and the JSON body of the error response
+fn error_response(e: JsonError) -> (StatusCode, Value) {
+    let response = Response::from(e);
+    assert_eq!(
+        response.headers().get("Content-Type"),
+        Some("application/json")
+    );
+
+    let mut bytes = Vec::new();
+    response.send(&mut bytes).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+    let body = text.split_once("\r\n\r\n").unwrap().1;
+    (response.status_code(), serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn accepts_json_media_types() {
+    for content_type in [
+        "application/json",
+        "application/json; charset=utf-8",
+        "Application/JSON",
+        "application/merge-patch+json",
+        "application/vnd.api+json; charset=utf-8",
+    ] {
+        let greet: Greet = post(Some(content_type), r#"{"name":"bbc"}"#)
+            .json()
+            .unwrap_or_else(|e| panic!("{content_type}: {e}"));
+        assert_eq!(greet.name, "bbc");
+    }
+}
+
+#[test]
+fn other_or_missing_content_type_is_unsupported() {
+    for content_type in [
+        None,
+        Some("text/plain"),
+        Some("application/x-www-form-urlencoded"),
+        Some("text/json+xml"),
+        Some("application/jsonp"),
+    ] {
+        let e = post(content_type, r#"{"name":"bbc"}"#)
+            .json::<Greet>()
+            .unwrap_err();
+        assert!(
+            matches!(e, JsonError::UnsupportedMediaType),
+            "{content_type:?}"
+        );
+
+        let (status_code, body) = error_response(e);
+        assert_eq!(status_code, StatusCode::UnsupportedMediaType);
+        assert_eq!(
+            body,
+            json!({ "error": "Expected Content-Type: application/json" })
+        );
+    }
+}
+
+#[test]
+fn malformed_body_reports_where() {
+    let body = "{\n  \"name\": \"bbc\",\n  oops\n}";
+    let e = post(Some("application/json"), body)
+        .json::<Greet>()
+        .unwrap_err();
+
+    let (status_code, body) = error_response(e);
+    assert_eq!(status_code, StatusCode::BadRequest);
+    assert_eq!((&body["line"], &body["column"]), (&json!(3), &json!(3)));
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Malformed JSON body"));
+}
+
+// Valid JSON of the wrong shape is a bad request too
+#[test]
+fn wrong_shape_is_a_bad_request() {
+    let e = post(Some("application/json"), r#"{"nom":"bbc"}"#)
+        .json::<Greet>()
+        .unwrap_err();
+
+    let (status_code, body) = error_response(e);
+    assert_eq!(status_code, StatusCode::BadRequest);
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .contains("missing field `name`"));
+}
@@ -0,0 +1,125 @@
+// Tests for the Prometheus metrics
+mod common;
+
+use common::{get, local_server, spawn_server};
+use http_server::http::{Method, Response, StatusCode};
+use http_server::metrics::Metrics;
+use http_server::router::Router;
+use std::panic;
+use std::time::Duration;
+
+#[test]
+fn renders_the_text_exposition_format() {
+    let metrics = Metrics::new();
+    let ms = Duration::from_millis;
+    metrics.record_request(Method::POST, StatusCode::Ok, ms(3));
+    metrics.record_request(Method::GET, StatusCode::NotFound, ms(20));
+    metrics.record_request(Method::GET, StatusCode::Ok, ms(3));
+    metrics.record_request(Method::GET, StatusCode::Ok, Duration::from_secs(20));
+    metrics.record_parse_error();
+
+    let expected = "\
+# HELP http_requests_total Requests handled, by method and status code.
+# TYPE http_requests_total counter
+http_requests_total{method=\"GET\",status=\"200\"} 2
+http_requests_total{method=\"GET\",status=\"404\"} 1
+http_requests_total{method=\"POST\",status=\"200\"} 1
+# HELP http_connections_in_flight Connections currently being handled.
+# TYPE http_connections_in_flight gauge
+http_connections_in_flight 0
+# HELP http_received_bytes_total Bytes read from clients.
+# TYPE http_received_bytes_total counter
+http_received_bytes_total 0
+# HELP http_sent_bytes_total Bytes written to clients.
+# TYPE http_sent_bytes_total counter
+http_sent_bytes_total 0
+# HELP http_parse_errors_total Requests that could not be parsed.
+# TYPE http_parse_errors_total counter
+http_parse_errors_total 1
+# HELP http_request_duration_seconds Time from accepting a connection to finishing the response.
+# TYPE http_request_duration_seconds histogram
+http_request_duration_seconds_bucket{le=\"0.005\"} 2
+http_request_duration_seconds_bucket{le=\"0.01\"} 2
+http_request_duration_seconds_bucket{le=\"0.025\"} 3
+http_request_duration_seconds_bucket{le=\"0.05\"} 3
+http_request_duration_seconds_bucket{le=\"0.1\"} 3
+http_request_duration_seconds_bucket{le=\"0.25\"} 3
+http_request_duration_seconds_bucket{le=\"0.5\"} 3
+http_request_duration_seconds_bucket{le=\"1\"} 3
+http_request_duration_seconds_bucket{le=\"2.5\"} 3
+http_request_duration_seconds_bucket{le=\"5\"} 3
+http_request_duration_seconds_bucket{le=\"10\"} 3
+http_request_duration_seconds_bucket{le=\"+Inf\"} 4
+http_request_duration_seconds_sum 20.026
+http_request_duration_seconds_count 4
+";
+    assert_eq!(metrics.render(), expected);
+}
+
+// Each bucket counts everything at or below its bound, so they never go down
+// and the +Inf bucket always equals _count
+#[test]
+fn histogram_buckets_are_cumulative() {
+    let metrics = Metrics::new();
+    for millis in [1, 5, 7, 60, 60, 400, 3_000, 9_999, 10_000, 10_001, 60_000] {
+        metrics.record_request(Method::GET, StatusCode::Ok, Duration::from_millis(millis));
+    }
+
+    let rendered = metrics.render();
+    let value = |line: &str| -> u64 { line.rsplit(' ').next().unwrap().parse().unwrap() };
+    let buckets: Vec<u64> = rendered
+        .lines()
+        .filter(|line| line.starts_with("http_request_duration_seconds_bucket"))
+        .map(value)
+        .collect();
+
+    assert_eq!(buckets, [2, 3, 3, 3, 5, 5, 6, 6, 6, 7, 9, 11]);
+    assert!(rendered.contains("http_request_duration_seconds_bucket{le=\"+Inf\"} 11\n"));
+    assert!(rendered.contains("http_request_duration_seconds_count 11\n"));
+    assert!(rendered.contains("http_request_duration_seconds_sum 93.533\n"));
+}
+
+#[test]
+fn in_flight_goes_down_when_the_connection_ends() {
+    let metrics = Metrics::new();
+    let gauge = |metrics: &Metrics| metrics.render().contains("http_connections_in_flight 0\n");
+
+    let first = metrics.connection();
+    let second = metrics.connection();
+    assert!(metrics.render().contains("http_connections_in_flight 2\n"));
+    drop(first);
+    drop(second);
+    assert!(gauge(&metrics));
+
+    // Unwinding drops the guard too
+    let result = panic::catch_unwind(|| {
+        let _in_flight = metrics.connection();
+        panic!("handler bug");
+    });
+    assert!(result.is_err());
+    assert!(gauge(&metrics));
+}
+
+// Through a real server: a handler that fails or panics still finishes its
+// connection, and the only one left in flight is the one asking for /metrics
+#[test]
+fn server_counts_failed_and_panicking_requests() {
+    let site = Router::new()
+        .get("/fail", |_| {
+            Response::new(StatusCode::InternalServerError, None)
+        })
+        .get("/panic", |_| panic!("handler bug"));
+    let addr = spawn_server(local_server().workers(1).metrics("/metrics"), site);
+
+    assert!(get(addr, "/fail").starts_with("HTTP/1.1 500 "));
+    // With a single worker, the requests after this one show it survived the panic
+    assert!(get(addr, "/panic").starts_with("HTTP/1.1 500 "));
+
+    let metrics = get(addr, "/metrics");
+    assert!(
+        metrics.contains("http_connections_in_flight 1\n"),
+        "{metrics}"
+    );
+    assert!(metrics.contains("http_requests_total{method=\"GET\",status=\"500\"} 2\n"));
+    assert!(metrics.contains("http_request_duration_seconds_count 2\n"));
+}
@@ -0,0 +1,117 @@
+// Tests for the token bucket rate limiter. The clock is passed in, so nothing sleeps.
+mod common;
+
+use common::{get, local_server, spawn_server};
+use http_server::http::{Response, StatusCode};
+use http_server::rate_limit::{too_many_requests, RateLimiter};
+use http_server::router::Router;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+fn client(ip: &str, port: u16) -> SocketAddr {
+    SocketAddr::new(ip.parse().unwrap(), port)
+}
+
+fn secs(seconds: f64) -> Duration {
+    Duration::from_secs_f64(seconds)
+}
+
+#[test]
+fn burst_then_retry_after() {
+    let limiter = RateLimiter::new(3, 0.5);
+    let start = Instant::now();
+    let alice = client("10.0.0.1", 1000);
+
+    for _ in 0..3 {
+        assert_eq!(limiter.check_at(alice, start), Ok(()));
+    }
+    // One token comes back every 2 seconds
+    assert_eq!(limiter.check_at(alice, start), Err(secs(2.0)));
+    assert_eq!(limiter.check_at(alice, start + secs(0.5)), Err(secs(1.5)));
+
+    // Other clients have their own bucket, other ports of the same one don't
+    assert_eq!(limiter.check_at(client("10.0.0.2", 1000), start), Ok(()));
+    assert!(limiter.check_at(client("10.0.0.1", 2000), start).is_err());
+}
+
+#[test]
+fn refills_over_time_up_to_the_burst() {
+    let limiter = RateLimiter::new(2, 1.0);
+    let start = Instant::now();
+    let alice = client("10.0.0.1", 1000);
+
+    assert!(limiter.check_at(alice, start).is_ok());
+    assert!(limiter.check_at(alice, start).is_ok());
+    assert!(limiter.check_at(alice, start).is_err());
+
+    // After a second there is exactly one token again
+    let later = start + secs(1.0);
+    assert!(limiter.check_at(alice, later).is_ok());
+    assert_eq!(limiter.check_at(alice, later), Err(secs(1.0)));
+
+    // A long break refills the bucket, but never past the burst
+    let much_later = later + secs(60.0);
+    assert!(limiter.check_at(alice, much_later).is_ok());
+    assert!(limiter.check_at(alice, much_later).is_ok());
+    assert!(limiter.check_at(alice, much_later).is_err());
+}
+
+#[test]
+fn evicts_idle_buckets() {
+    let limiter = RateLimiter::new(1, 1.0).idle_timeout(secs(10.0));
+    let start = Instant::now();
+
+    limiter.check_at(client("10.0.0.1", 1000), start).unwrap();
+    limiter
+        .check_at(client("10.0.0.2", 1000), start + secs(5.0))
+        .unwrap();
+    assert_eq!(limiter.clients(), 2);
+
+    // Only the first client has been quiet for the whole timeout
+    limiter
+        .check_at(client("10.0.0.3", 1000), start + secs(12.0))
+        .unwrap();
+    assert_eq!(limiter.clients(), 2);
+
+    // Everybody has gone quiet, the client coming back starts over with a new bucket
+    limiter
+        .check_at(client("10.0.0.3", 1000), start + secs(30.0))
+        .unwrap();
+    assert_eq!(limiter.clients(), 1);
+}
+
+// The wait is sent in whole seconds, rounded up so the client doesn't come back too soon
+#[test]
+fn retry_after_rounds_up_to_whole_seconds() {
+    let start = Instant::now();
+    let alice = client("10.0.0.1", 1000);
+    let retry_after = |limiter: &RateLimiter, now| {
+        let wait = limiter.check_at(alice, now).unwrap_err();
+        let response = too_many_requests(wait);
+        assert_eq!(response.status_code(), StatusCode::TooManyRequests);
+        response.headers().get("Retry-After").unwrap().to_string()
+    };
+
+    // 3.33 seconds to go
+    let limiter = RateLimiter::new(1, 0.3);
+    limiter.check_at(alice, start).unwrap();
+    assert_eq!(retry_after(&limiter, start), "4");
+
+    // Exactly 2 seconds, then 1.5, then a tenth of a second that still counts as a whole one
+    let limiter = RateLimiter::new(1, 0.5);
+    limiter.check_at(alice, start).unwrap();
+    assert_eq!(retry_after(&limiter, start), "2");
+    assert_eq!(retry_after(&limiter, start + secs(0.5)), "2");
+    assert_eq!(retry_after(&limiter, start + secs(1.9)), "1");
+}
+
+#[test]
+fn server_answers_429_when_over_the_limit() {
+    let site = Router::new().get("/", |_| Response::new(StatusCode::Ok, None));
+    let addr = spawn_server(local_server().rate_limit(RateLimiter::new(1, 0.3)), site);
+
+    assert!(get(addr, "/").starts_with("HTTP/1.1 200 "));
+    let response = get(addr, "/");
+    assert!(response.starts_with("HTTP/1.1 429 "), "{response}");
+    assert!(response.contains("Retry-After: "), "{response}");
+}
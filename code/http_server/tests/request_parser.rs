@@ -0,0 +1,244 @@
+// Property tests for the hand-written request parser
+use http_server::http::{ParseError, Request};
+use proptest::prelude::*;
+use std::io::{Read, Result as IoResult};
+
+// The parts of a well-formed request, before they are turned into bytes
+#[derive(Debug, Clone)]
+struct RawRequest {
+    method: &'static str,
+    path: String,
+    query_string: Option<String>,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl RawRequest {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = format!("{} {}", self.method, self.path).into_bytes();
+        if let Some(query_string) = &self.query_string {
+            bytes.extend_from_slice(format!("?{}", query_string).as_bytes());
+        }
+        bytes.extend_from_slice(b" HTTP/1.1\r\n");
+        for (name, value) in &self.headers {
+            bytes.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+        if !self.body.is_empty() {
+            bytes.extend_from_slice(format!("Content-Length: {}\r\n", self.body.len()).as_bytes());
+        }
+        bytes.extend_from_slice(b"\r\n");
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+fn method() -> impl Strategy<Value = &'static str> {
+    prop::sample::select(vec!["GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS"])
+}
+
+fn header() -> impl Strategy<Value = (String, String)> {
+    // Values are trimmed by the parser, so they never start or end with a space here
+    ("[A-Za-z][A-Za-z0-9-]{0,15}", "([!-~]([ -~]{0,20}[!-~])?)?").prop_filter(
+        "length headers are added separately",
+        |(name, _)| {
+            !name.eq_ignore_ascii_case("Content-Length")
+                && !name.eq_ignore_ascii_case("Transfer-Encoding")
+        },
+    )
+}
+
+fn raw_request() -> impl Strategy<Value = RawRequest> {
+    (
+        method(),
+        "/[A-Za-z0-9_./-]{0,30}",
+        prop::option::of("[A-Za-z0-9=&%+]{0,20}"),
+        prop::collection::vec(header(), 0..8),
+        prop::collection::vec(any::<u8>(), 0..64),
+    )
+        .prop_map(|(method, path, query_string, headers, body)| RawRequest {
+            method,
+            path,
+            query_string,
+            headers,
+            body,
+        })
+}
+
+#[derive(Debug, Clone)]
+enum Mutation {
+    Flip(usize, u8),
+    Insert(usize, u8),
+    Remove(usize),
+    Truncate(usize),
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    prop_oneof![
+        (any::<usize>(), any::<u8>()).prop_map(|(i, b)| Mutation::Flip(i, b)),
+        (any::<usize>(), any::<u8>()).prop_map(|(i, b)| Mutation::Insert(i, b)),
+        any::<usize>().prop_map(Mutation::Remove),
+        any::<usize>().prop_map(Mutation::Truncate),
+    ]
+}
+
+fn mutate(bytes: &mut Vec<u8>, mutation: &Mutation) {
+    if bytes.is_empty() {
+        return;
+    }
+    match *mutation {
+        Mutation::Flip(i, b) => {
+            let i = i % bytes.len();
+            bytes[i] ^= b;
+        }
+        Mutation::Insert(i, b) => bytes.insert(i % (bytes.len() + 1), b),
+        Mutation::Remove(i) => {
+            bytes.remove(i % bytes.len());
+        }
+        Mutation::Truncate(i) => bytes.truncate(i % bytes.len()),
+    }
+}
+
+// Hands out the data a few bytes at a time, like a slow network connection
+struct Trickle<'a> {
+    data: &'a [u8],
+    step: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let n = self.step.min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+proptest! {
+    #[test]
+    fn never_panics_on_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = Request::try_from(bytes.as_slice());
+    }
+
+    #[test]
+    fn never_panics_on_mutated_requests(
+        raw in raw_request(),
+        mutations in prop::collection::vec(mutation(), 1..8),
+    ) {
+        let mut bytes = raw.to_bytes();
+        for m in &mutations {
+            mutate(&mut bytes, m);
+        }
+        let _ = Request::try_from(bytes.as_slice());
+    }
+
+    #[test]
+    fn parses_valid_requests(raw in raw_request()) {
+        let request = Request::try_from(raw.to_bytes().as_slice()).unwrap();
+
+        prop_assert_eq!(request.method().to_string(), raw.method);
+        prop_assert_eq!(request.path(), raw.path.as_str());
+        prop_assert_eq!(request.query_string(), raw.query_string.as_deref());
+        prop_assert_eq!(request.body(), raw.body.as_slice());
+        for (name, value) in &raw.headers {
+            prop_assert!(request.headers().get_all(name).any(|v| v == value));
+        }
+    }
+
+    #[test]
+    fn round_trips_through_serialization(raw in raw_request()) {
+        let request = Request::try_from(raw.to_bytes().as_slice()).unwrap();
+        let reparsed = Request::try_from(request.to_bytes().as_slice()).unwrap();
+
+        prop_assert_eq!(request, reparsed);
+    }
+
+    #[test]
+    fn reads_requests_split_over_many_reads(raw in raw_request(), step in 1..16usize) {
+        let bytes = raw.to_bytes();
+        let mut stream = Trickle { data: &bytes, step };
+
+        let request = Request::read_from(&mut stream).unwrap();
+        prop_assert_eq!(request, Request::try_from(bytes.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn rejects_duplicate_content_length(raw in raw_request(), a in 0..100usize, b in 0..100usize) {
+        let mut raw = raw;
+        raw.body.clear();
+        raw.headers.push(("Content-Length".to_string(), a.to_string()));
+        raw.headers.push(("content-length".to_string(), b.to_string()));
+
+        let result = Request::try_from(raw.to_bytes().as_slice());
+        prop_assert!(matches!(result, Err(ParseError::ConflictingLength)));
+    }
+
+    #[test]
+    fn rejects_transfer_encoding_with_content_length(raw in raw_request()) {
+        let mut raw = raw;
+        raw.body = b"0\r\n\r\n".to_vec();
+        raw.headers.push(("Transfer-Encoding".to_string(), "chunked".to_string()));
+
+        let result = Request::try_from(raw.to_bytes().as_slice());
+        prop_assert!(matches!(result, Err(ParseError::ConflictingLength)));
+    }
+}
+
+#[test]
+fn rejects_smuggling_tricks() {
+    let cases: [&[u8]; 5] = [
+        b"POST / HTTP/1.1\r\nContent-Length: +5\r\n\r\nhello",
+        b"POST / HTTP/1.1\r\nContent-Length: 5, 5\r\n\r\nhello",
+        b"POST / HTTP/1.1\r\nContent-Length : 5\r\n\r\nhello",
+        b"POST / HTTP/1.1\r\nX-Foo: bar\nContent-Length: 5\r\n\r\nhello",
+        b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+    ];
+
+    for case in cases {
+        assert!(
+            Request::try_from(case).is_err(),
+            "accepted {:?}",
+            String::from_utf8_lossy(case)
+        );
+    }
+}
+
+// A big body a few bytes at a time, with the head's \r\n\r\n split over two reads
+#[test]
+fn reads_a_large_request_in_small_chunks() {
+    let body: Vec<u8> = (0..=255u8).cycle().take(512 * 1024).collect();
+    let mut bytes = format!(
+        "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    let head_len = bytes.len();
+    bytes.extend_from_slice(&body);
+
+    for step in [3, 7, 1000] {
+        let mut stream = Trickle { data: &bytes, step };
+        let request = Request::read_from(&mut stream).unwrap();
+        assert_eq!(request.path(), "/upload");
+        assert_eq!(request.body(), body.as_slice());
+    }
+
+    // The connection closing halfway through the body
+    let mut stream = Trickle {
+        data: &bytes[..head_len + 100],
+        step: 7,
+    };
+    assert!(matches!(
+        Request::read_from(&mut stream),
+        Err(ParseError::Incomplete)
+    ));
+
+    // A head that never ends
+    let endless = vec![b'a'; 2 * 1024 * 1024];
+    let mut stream = Trickle {
+        data: &endless,
+        step: 1000,
+    };
+    assert!(matches!(
+        Request::read_from(&mut stream),
+        Err(ParseError::TooLarge)
+    ));
+}
@@ -0,0 +1,161 @@
+// Tests for cookies, and the signed session cookies built on them
+use http_server::http::{cookie, ParseError, Request, SameSite, SetCookie};
+use http_server::session::{MemorySessionStore, Session, SessionManager, SessionStore};
+use std::io::{Error, ErrorKind, Read, Result as IoResult};
+use std::time::Duration;
+
+const KEY: &[u8] = b"test key";
+
+#[test]
+fn parses_cookie_headers() {
+    let pairs: Vec<_> =
+        cookie::parse(r#"theme=dark; session="abc=123";  lang = en ;; =x; flag"#).collect();
+    assert_eq!(
+        pairs,
+        [("theme", "dark"), ("session", "abc=123"), ("lang", "en")]
+    );
+
+    // Browsers may split cookies over several headers
+    let raw = b"GET / HTTP/1.1\r\nCookie: theme=dark\r\nCookie: session=abc\r\n\r\n";
+    let request = Request::try_from(&raw[..]).unwrap();
+    assert_eq!(request.cookie("session"), Some("abc"));
+    assert_eq!(request.cookie("theme"), Some("dark"));
+    assert_eq!(request.cookie("missing"), None);
+}
+
+#[test]
+fn set_cookie_output() {
+    let cookie = SetCookie::new("session", "abc")
+        .path("/")
+        .max_age(Duration::from_secs(3600))
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax);
+    assert_eq!(
+        cookie.to_string(),
+        "session=abc; Path=/; Max-Age=3600; HttpOnly; Secure; SameSite=Lax"
+    );
+
+    assert_eq!(SetCookie::new("theme", "dark").to_string(), "theme=dark");
+    assert_eq!(
+        SetCookie::removal("session").to_string(),
+        "session=; Max-Age=0"
+    );
+}
+
+// Nothing a handler puts in a cookie can add attributes or start a new header
+#[test]
+fn set_cookie_encodes_separators() {
+    let cookie =
+        SetCookie::new("a=b;", "x; Domain=evil.com\r\nSet-Cookie: admin=1").path("/;Secure");
+    assert_eq!(
+        cookie.to_string(),
+        "a%3Db%3B=x%3B%20Domain=evil.com%0D%0ASet-Cookie:%20admin=1; Path=/%3BSecure"
+    );
+
+    // `%` is encoded too, so an encoded value can't be mistaken for a plain one
+    assert_eq!(SetCookie::new("n", "100%").to_string(), "n=100%25");
+    assert_eq!(SetCookie::new("n", "héllo").to_string(), "n=h%C3%A9llo");
+}
+
+fn request_with(cookie: &str) -> Request {
+    let raw = format!("GET / HTTP/1.1\r\nCookie: {}\r\n\r\n", cookie);
+    Request::try_from(raw.as_bytes()).unwrap()
+}
+
+// The `name=value` part of a Set-Cookie, as the browser sends it back
+fn sent_back(cookie: &SetCookie) -> String {
+    cookie.to_string().split(';').next().unwrap().to_string()
+}
+
+fn session(username: &str) -> Session {
+    Session::from([("username".to_string(), username.to_string())])
+}
+
+#[test]
+fn signed_session_round_trip() {
+    let sessions = SessionManager::new(MemorySessionStore::new(), KEY);
+    let cookie = sessions.start(session("bbc"));
+    let request = request_with(&sent_back(&cookie));
+
+    let (_, loaded) = sessions.load(&request).unwrap();
+    assert_eq!(loaded["username"], "bbc");
+
+    // Ending it forgets the session, even if the browser keeps sending the cookie
+    assert!(sent_back(&sessions.end(&request)).ends_with('='));
+    assert!(sessions.load(&request).is_none());
+}
+
+#[test]
+fn rejects_tampered_cookies() {
+    let sessions = SessionManager::new(MemorySessionStore::new(), KEY);
+    let value = sent_back(&sessions.start(session("bbc")));
+    let (id, signature) = value.rsplit_once('.').unwrap();
+
+    // A different id with the old signature
+    let mut forged_id = id.to_string();
+    forged_id.replace_range(8..9, if &id[8..9] == "0" { "1" } else { "0" });
+    assert!(sessions
+        .load(&request_with(&format!("{forged_id}.{signature}")))
+        .is_none());
+
+    // A flipped signature, a missing one, and one that isn't hex
+    let mut flipped = signature.to_string();
+    flipped.replace_range(0..1, if &signature[..1] == "0" { "1" } else { "0" });
+    for cookie in [
+        format!("{id}.{flipped}"),
+        id.to_string(),
+        format!("{id}.zz"),
+    ] {
+        assert!(sessions.load(&request_with(&cookie)).is_none(), "{cookie}");
+    }
+
+    // Signed with another key
+    let other = SessionManager::new(MemorySessionStore::new(), b"other key");
+    assert!(other.load(&request_with(&value)).is_none());
+}
+
+#[test]
+fn rejects_expired_sessions() {
+    let sessions = SessionManager::new(MemorySessionStore::new(), KEY).max_age(Duration::ZERO);
+    let cookie = sessions.start(session("bbc"));
+    assert!(sessions.load(&request_with(&sent_back(&cookie))).is_none());
+}
+
+// Sessions nobody comes back for are swept out as new ones are saved
+#[test]
+fn sweeps_expired_sessions() {
+    let store = MemorySessionStore::new();
+    for i in 0..1000 {
+        store.save(&format!("expired {i}"), Session::new(), Duration::ZERO);
+    }
+    assert!(store.len() < 100, "{} sessions kept", store.len());
+
+    let store = MemorySessionStore::new();
+    for i in 0..1000 {
+        store.save(
+            &format!("live {i}"),
+            Session::new(),
+            Duration::from_secs(60),
+        );
+    }
+    assert_eq!(store.len(), 1000);
+    assert!(store.load("live 0").is_some());
+}
+
+// Stands in for a client that connects and then goes quiet
+struct Idle;
+
+impl Read for Idle {
+    fn read(&mut self, _: &mut [u8]) -> IoResult<usize> {
+        Err(Error::from(ErrorKind::WouldBlock))
+    }
+}
+
+#[test]
+fn idle_clients_time_out() {
+    assert!(matches!(
+        Request::read_from(&mut Idle),
+        Err(ParseError::Timeout)
+    ));
+}
@@ -0,0 +1,132 @@
+// Tests for picking a site by Host header, and a route by method and path
+mod common;
+
+use common::{local_server, send, spawn_server};
+use http_server::http::{Method, Request, Response, StatusCode};
+use http_server::router::{Router, VirtualHosts};
+use http_server::server::Handler;
+
+// A site that answers every request with its own name
+fn site(name: &'static str) -> Router {
+    Router::new().get("/", move |_| {
+        Response::new(StatusCode::Ok, Some(name.to_string()))
+    })
+}
+
+fn hosts() -> VirtualHosts {
+    VirtualHosts::new()
+        .host("example.com", site("exact"))
+        .host("*.example.com", site("wildcard"))
+        .host("*.api.example.com", site("longer wildcard"))
+        .default_host(site("default"))
+}
+
+fn get(handler: &impl Handler, headers: &str) -> Response {
+    let raw = format!("GET / HTTP/1.1\r\n{}\r\n", headers);
+    handler.handle_request(&Request::try_from(raw.as_bytes()).unwrap())
+}
+
+// Which site answered, or the status code if none did
+fn site_for(host: &str) -> String {
+    let response = get(&hosts(), &format!("Host: {}\r\n", host));
+    match response.status_code() {
+        StatusCode::Ok => body(&response),
+        status => status.to_string(),
+    }
+}
+
+fn body(response: &Response) -> String {
+    let mut bytes = Vec::new();
+    response.send(&mut bytes).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+    text.split_once("\r\n\r\n").unwrap().1.to_string()
+}
+
+#[test]
+fn exact_then_longest_wildcard_then_default() {
+    assert_eq!(site_for("example.com"), "exact");
+    assert_eq!(site_for("www.example.com"), "wildcard");
+    assert_eq!(site_for("v1.api.example.com"), "longer wildcard");
+    // A wildcard never matches its bare domain, or a name that only ends the same way
+    assert_eq!(site_for("api.example.com"), "wildcard");
+    assert_eq!(site_for("badexample.com"), "default");
+    assert_eq!(site_for("other.org"), "default");
+}
+
+#[test]
+fn without_a_default_unknown_hosts_are_not_found() {
+    let hosts = VirtualHosts::new().host("example.com", site("exact"));
+    let response = get(&hosts, "Host: other.org\r\n");
+    assert_eq!(response.status_code(), StatusCode::NotFound);
+}
+
+#[test]
+fn port_and_trailing_dot_are_ignored() {
+    assert_eq!(site_for("example.com:8080"), "exact");
+    assert_eq!(site_for("example.com."), "exact");
+    assert_eq!(site_for("www.example.com.:443"), "wildcard");
+    // An IPv6 address keeps its colons, only the port goes
+    assert_eq!(site_for("[::1]:8080"), "default");
+}
+
+#[test]
+fn host_names_are_case_insensitive() {
+    assert_eq!(site_for("EXAMPLE.com"), "exact");
+    assert_eq!(site_for("Www.Example.COM"), "wildcard");
+
+    let hosts = VirtualHosts::new().host("Mixed.Example.com", site("mixed"));
+    assert_eq!(body(&get(&hosts, "Host: mixed.example.COM\r\n")), "mixed");
+}
+
+#[test]
+fn missing_empty_or_duplicate_host_is_a_bad_request() {
+    for headers in ["", "Host: \r\n", "Host: example.com\r\nHost: other.org\r\n"] {
+        let response = get(&hosts(), headers);
+        assert_eq!(
+            response.status_code(),
+            StatusCode::BadRequest,
+            "{headers:?}"
+        );
+    }
+}
+
+#[test]
+fn wrong_method_lists_the_allowed_ones() {
+    let router = Router::new()
+        .get("/login", |_| Response::new(StatusCode::Ok, None))
+        .post("/login", |_| Response::new(StatusCode::Ok, None));
+
+    let raw = b"DELETE /login HTTP/1.1\r\nHost: example.com\r\n\r\n";
+    let response = router.handle_request(&Request::try_from(&raw[..]).unwrap());
+    assert_eq!(response.status_code(), StatusCode::MethodNotAllowed);
+    assert_eq!(response.headers().get("Allow"), Some("GET, POST"));
+
+    let raw = b"GET /logout HTTP/1.1\r\nHost: example.com\r\n\r\n";
+    let response = router.handle_request(&Request::try_from(&raw[..]).unwrap());
+    assert_eq!(response.status_code(), StatusCode::NotFound);
+    assert_eq!(response.headers().get("Allow"), None);
+
+    let raw = b"POST /login HTTP/1.1\r\nHost: example.com\r\n\r\n";
+    let request = Request::try_from(&raw[..]).unwrap();
+    assert_eq!(request.method(), Method::POST);
+    assert_eq!(
+        router.handle_request(&request).status_code(),
+        StatusCode::Ok
+    );
+}
+
+// The server answers /metrics itself, it still needs a good Host header
+#[test]
+fn metrics_need_a_host_too() {
+    let addr = spawn_server(local_server().metrics("/metrics"), hosts());
+
+    let response = send(addr, "GET /metrics HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 400 "), "{response}");
+
+    let response = send(addr, "GET /metrics HTTP/1.1\r\nHost: a\r\nHost: b\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 400 "), "{response}");
+
+    let response = send(addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+    assert!(response.contains("http_requests_total"));
+}
@@ -0,0 +1,68 @@
+// The word counting, shared by the command line in main.rs and helloworld-web's /wordcount
+use std::collections::HashMap;
+use std::io::BufRead;
+
+// Counts how often each of `common_words` occurs in the text read from `reader`.
+// Matching ignores case, so the keys of the result are the lowercased search words.
+pub fn find_word_occurances<R: BufRead>(
+    reader: R,
+    common_words: &[String],
+) -> Result<HashMap<String, usize>, std::io::Error> {
+    let mut wordcount_map: HashMap<String, usize> = HashMap::new();
+
+    // we will read 1000 lines from a file and process them
+    let buffer_limit: usize = 1000; // number of lines to read
+    let mut lines_buffer: Vec<String> = Vec::new();
+
+    for line in reader.lines() {
+        // check if we have reached the buffer limit
+        if lines_buffer.len() >= buffer_limit {
+            // process the buffer contents
+            let result = find_word_count_in_buffer(&lines_buffer, common_words);
+
+            // update the wordcount_map with the values from result
+            for (word_key, word_count) in result.iter() {
+                *wordcount_map.entry(word_key.clone()).or_insert(0) += word_count;
+            }
+
+            lines_buffer.clear();
+        }
+        lines_buffer.push(line?);
+    }
+
+    // process the last buffer, this also fills in the zero counts when the text is empty
+    let result = find_word_count_in_buffer(&lines_buffer, common_words);
+    for (word_key, word_count) in result.iter() {
+        *wordcount_map.entry(word_key.clone()).or_insert(0) += word_count;
+    }
+
+    Ok(wordcount_map)
+}
+
+pub fn find_word_count_in_buffer(
+    lines_buffer: &[String],
+    search_words: &[String],
+) -> HashMap<String, usize> {
+    let mut wordcount_map: HashMap<String, usize> = HashMap::new();
+
+    for word in search_words {
+        wordcount_map.insert(word.to_lowercase(), 0);
+    }
+
+    for line in lines_buffer {
+        let cleaned_line: String = line
+            .to_lowercase()
+            .chars()
+            .filter(|c| *c != ',' && *c != '!' && *c != '.')
+            .collect();
+
+        // parse line and get words
+        for word in cleaned_line.split_whitespace() {
+            if let Some(count) = wordcount_map.get_mut(word.trim()) {
+                *count += 1;
+            }
+        }
+    }
+
+    wordcount_map
+}
@@ -1,97 +1,29 @@
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::BufReader;
+use std::path::PathBuf;
 
-use std::io::Error;
+use word_count_problem::find_word_occurances;
 
-fn main() {
+// Usage: word_count_problem [FILE] [WORD]...
+// Without arguments, counts "Gutenberg" and "travel" in Gulliver's Travels
+fn main() -> Result<(), std::io::Error> {
     println!(" === Word Count Occurance finder ===");
 
-    let common_words: Vec<String> = vec![String::from("Gutenberg"), String::from("travel")];
+    let mut args = std::env::args().skip(1);
+    let file_path = args.next().map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/gullivers_travels.txt")
+    });
 
-    let wc_result: HashMap<String, i32> = find_word_occurances(&common_words).unwrap();
-    println!("Total Word count: {:?}", wc_result);
-}
-
-fn find_word_occurances(
-    common_words: &Vec<String>,
-) -> Result<HashMap<String, i32>, std::io::Error> {
-    let mut wordcount_map: HashMap<String, i32> = HashMap::new();
-
-    // Demo - Buffered reading
-    let file_path = Path::new("./gullivers_travels.txt");
-    let fp = File::open(file_path)?;
-    let reader = BufReader::new(fp);
-
-    // we will read 1000 lines from a file and process them
-    let buffer_limit: i32 = 1000; // number of lines to read
-    let mut line_count: i32 = 0;
-
-    let mut lines_buffer: Vec<String> = Vec::new();
-
-    for (_i, line) in reader.lines().enumerate() {
-        // check if we have reached the buffer limit
-        if line_count >= buffer_limit {
-            // process the buffer contents
-            let result: HashMap<String, i32> =
-                find_word_count_in_buffer(&lines_buffer, &common_words)?;
-
-            // update the wordcount_map with the values from result
-            for (word_key, word_count) in result.iter() {
-                *wordcount_map.entry(word_key.clone()).or_insert(0) += word_count;
-            }
-
-            line_count = 0;
-            lines_buffer.clear();
-        }
-        let line = line?;
-        lines_buffer.push(line);
-        line_count += 1;
-    }
-
-    if !lines_buffer.is_empty() {
-        // process the last buffer
-        let result = find_word_count_in_buffer(&lines_buffer, &common_words).unwrap();
-
-        // update the wordcount_map with the values from result
-        for (word_key, word_count) in result.iter() {
-            *wordcount_map.entry(word_key.clone()).or_insert(0) += word_count;
-        }
-    }
-
-    Ok(wordcount_map)
-}
-
-fn find_word_count_in_buffer(
-    lines_buffer: &Vec<String>,
-    search_words: &Vec<String>,
-) -> Result<HashMap<String, i32>, Error> {
-    let mut wordcount_map: HashMap<String, i32> = HashMap::new();
-
-    for word in search_words {
-        wordcount_map.insert(word.to_lowercase(), 0);
+    let mut common_words: Vec<String> = args.collect();
+    if common_words.is_empty() {
+        common_words = vec![String::from("Gutenberg"), String::from("travel")];
     }
 
-    for line in lines_buffer {
-        let cleaned_line: String = line
-            .to_lowercase()
-            .chars()
-            .filter(|c| *c != ',' && *c != '!' && *c != '.')
-            .collect();
-
-        // parse line and get words
-        let words_in_line: Vec<&str> = cleaned_line.split_whitespace().collect();
-
-        for word in words_in_line {
-            let word_check: String = word.trim().to_string();
-
-            if wordcount_map.contains_key(&word_check) {
-                *wordcount_map.entry(word_check.clone()).or_insert(0) += 1;
-            }
-        }
-    }
+    // Demo - Buffered reading
+    let reader = BufReader::new(File::open(&file_path)?);
 
-    println!("Word count: {:?}", wordcount_map);
-    Ok(wordcount_map)
+    let wc_result: HashMap<String, usize> = find_word_occurances(reader, &common_words)?;
+    println!("Total Word count: {:?}", wc_result);
+    Ok(())
 }
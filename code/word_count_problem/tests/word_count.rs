@@ -0,0 +1,33 @@
+use std::io::BufRead;
+use word_count_problem::find_word_occurances;
+
+fn words(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+}
+
+#[test]
+fn counts_ignore_case_and_punctuation() {
+    let text = "Travel, travel! TRAVEL.\nTravelling is not travel";
+    let counts = find_word_occurances(text.as_bytes(), &words(&["Travel", "missing"])).unwrap();
+
+    assert_eq!(counts["travel"], 4);
+    assert_eq!(counts["missing"], 0);
+}
+
+#[test]
+fn counts_across_buffers() {
+    // More lines than fit in one 1000 line buffer
+    let text = "the end\n".repeat(2500);
+    let counts = find_word_occurances(text.as_bytes(), &words(&["end"])).unwrap();
+    assert_eq!(counts["end"], 2500);
+}
+
+#[test]
+fn counts_the_book() {
+    let book = include_str!("../src/gullivers_travels.txt");
+    let lines = book.as_bytes().lines().count();
+    assert!(lines > 1000);
+
+    let counts = find_word_occurances(book.as_bytes(), &words(&["Gutenberg"])).unwrap();
+    assert!(counts["gutenberg"] > 0);
+}